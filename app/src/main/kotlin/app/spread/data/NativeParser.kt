@@ -24,6 +24,37 @@ object NativeParser {
      */
     external fun parseEpubWithConfig(data: ByteArray, maxChunkChars: Int): NativeBook?
 
+    /**
+     * Like [parseEpubWithConfig], but with all words packed into one IntArray
+     * and a UTF-8 text pool instead of one NativeWord object per word.
+     * Returns null on parse failure.
+     */
+    external fun parseEpubFlat(data: ByteArray, maxChunkChars: Int): NativeBookFlat?
+
+    /**
+     * Fraction of the book (0.0-1.0) read at a global word offset.
+     */
+    external fun readingProgress(totalWords: Int, wordOffset: Int): Float
+
+    /**
+     * Nearest global word offset for a progress fraction (0.0-1.0).
+     */
+    external fun wordOffsetForProgress(totalWords: Int, fraction: Float): Int
+
+    /**
+     * Global word offset of a (chapter, word) position.
+     * @param chapterWordCounts [NativeBookStats.chapterWordCounts]
+     * Returns -1 if the counts are invalid (negative or overflowing).
+     */
+    external fun positionToOffset(chapterWordCounts: IntArray, chapter: Int, word: Int): Int
+
+    /**
+     * Inverse of [positionToOffset]: `[chapter, word]` for a global word offset.
+     * Offsets past the end give the end of the last chapter.
+     * Returns null if the counts are invalid.
+     */
+    external fun offsetToPosition(chapterWordCounts: IntArray, offset: Int): IntArray?
+
     /**
      * Get the native library version.
      */
@@ -41,7 +72,10 @@ data class NativeBook(
 
 data class NativeBookMetadata(
     val title: String,
-    val author: String?
+    val authors: Array<String>,
+    val series: String?,
+    val seriesIndex: Float?,
+    val epubVersion: Int  // 2, 3, or 0 if unknown
 )
 
 data class NativeChapter(
@@ -54,18 +88,48 @@ data class NativeChapter(
 data class NativeWord(
     val text: String,
     val lengthBucket: Int,  // 0=short, 1=medium, 2=long, 3=veryLong
-    val followingPunct: Int // 0=none, 1=comma, 2=period, 3=paragraph
+    val followingPunct: Int // 0=none, 1=comma, 2=period, 3=exclamation, 4=question, 5=paragraph
 )
 
 data class NativeChapterStats(
     val wordCount: Int,
     val lengthCounts: IntArray,  // [short, medium, long, veryLong]
-    val punctCounts: IntArray    // [none, comma, period, paragraph]
+    val punctCounts: IntArray    // [none, comma, period, exclamation, question, paragraph]
 )
 
+// SYNC: Constructor must match the NativeBookStats signature in rust/src/jni.rs
 data class NativeBookStats(
     val totalWords: Int,
-    val aggregated: NativeChapterStats
+    val aggregated: NativeChapterStats,
+    val chapterWordCounts: IntArray  // wordCount of each chapter, e.g. for progress bar segments
+)
+
+/**
+ * Flat variant of [NativeBook]. Word i occupies
+ * `words[i * FLAT_WORD_FIELDS until (i + 1) * FLAT_WORD_FIELDS]`:
+ * (textPool offset, UTF-8 byte length, lengthBucket, followingPunct), with
+ * the same codes as [NativeWord].
+ */
+class NativeBookFlat(
+    val metadata: NativeBookMetadata,
+    val chapters: Array<NativeChapterFlat>,
+    val stats: NativeBookStats,
+    val textPool: ByteArray,
+    val words: IntArray
+)
+
+/**
+ * Ints per word in [NativeBookFlat.words].
+ * SYNC: Must match FLAT_WORD_FIELDS in rust/src/jni.rs
+ */
+const val FLAT_WORD_FIELDS = 4
+
+data class NativeChapterFlat(
+    val index: Int,
+    val title: String,
+    val wordStart: Int,  // index of the chapter's first word in NativeBookFlat.words
+    val wordCount: Int,
+    val stats: NativeChapterStats
 )
 
 // --- Conversion to domain types ---
@@ -76,7 +140,7 @@ fun NativeBook.toDomain(id: String): Book {
         id = BookId(id),
         metadata = BookMetadata(
             title = metadata.title,
-            author = metadata.author,
+            author = metadata.authors.joinToString(", ").ifEmpty { null },
             coverPath = null
         ),
         chapters = domainChapters,
@@ -84,6 +148,50 @@ fun NativeBook.toDomain(id: String): Book {
     )
 }
 
+/**
+ * Words are decoded from the flat arrays on access rather than copied into
+ * one object each, so the book stays O(chapters) objects on the heap.
+ */
+fun NativeBookFlat.toDomain(id: String): Book {
+    val domainChapters = chapters.map { chapter ->
+        val domainWords = FlatWordList(this, chapter.wordStart, chapter.wordCount)
+        Chapter(
+            index = chapter.index,
+            title = chapter.title,
+            words = domainWords,
+            stats = ChapterStats.fromWords(domainWords)
+        )
+    }
+    return Book(
+        id = BookId(id),
+        metadata = BookMetadata(
+            title = metadata.title,
+            author = metadata.authors.joinToString(", ").ifEmpty { null },
+            coverPath = null
+        ),
+        chapters = domainChapters,
+        stats = BookStats.fromChapters(domainChapters)
+    )
+}
+
+/** A chapter's words in [NativeBookFlat], decoded one at a time by [get]. */
+private class FlatWordList(
+    private val book: NativeBookFlat,
+    private val wordStart: Int,
+    override val size: Int
+) : AbstractList<Word>() {
+    override fun get(index: Int): Word {
+        if (index !in 0 until size) throw IndexOutOfBoundsException("Word $index of $size")
+        val base = (wordStart + index) * FLAT_WORD_FIELDS
+        val words = book.words
+        return Word(
+            text = String(book.textPool, words[base], words[base + 1], Charsets.UTF_8),
+            lengthBucket = lengthBucketOf(words[base + 2]),
+            followingPunct = punctuationOf(words[base + 3])
+        )
+    }
+}
+
 fun NativeChapter.toDomain(): Chapter {
     val domainWords = words.map { it.toDomain() }
     return Chapter(
@@ -97,17 +205,21 @@ fun NativeChapter.toDomain(): Chapter {
 fun NativeWord.toDomain(): Word {
     return Word(
         text = text,
-        lengthBucket = when (lengthBucket) {
-            0 -> LengthBucket.SHORT
-            1 -> LengthBucket.MEDIUM
-            2 -> LengthBucket.LONG
-            else -> LengthBucket.VERY_LONG
-        },
-        followingPunct = when (followingPunct) {
-            1 -> Punctuation.COMMA
-            2 -> Punctuation.PERIOD
-            3 -> Punctuation.PARAGRAPH
-            else -> null
-        }
+        lengthBucket = lengthBucketOf(lengthBucket),
+        followingPunct = punctuationOf(followingPunct)
     )
 }
+
+private fun lengthBucketOf(code: Int): LengthBucket = when (code) {
+    0 -> LengthBucket.SHORT
+    1 -> LengthBucket.MEDIUM
+    2 -> LengthBucket.LONG
+    else -> LengthBucket.VERY_LONG
+}
+
+private fun punctuationOf(code: Int): Punctuation? = when (code) {
+    1 -> Punctuation.COMMA
+    2, 3, 4 -> Punctuation.PERIOD
+    5 -> Punctuation.PARAGRAPH
+    else -> null
+}
@@ -61,6 +61,7 @@ data class TimingSettings(
         /**
          * Bucket-based multipliers for O(1) effective WPM calculation.
          * Approximates sqrt(avgBucketLength / AVG_WORD_LENGTH).
+         * SYNC: Must match LENGTH_MULTIPLIERS in rust/src/types.rs
          */
         const val SHORT_WORD_MULTIPLIER = 0.76f   // avg ~3 chars
         const val MEDIUM_WORD_MULTIPLIER = 1.12f  // avg ~6.5 chars
@@ -69,7 +70,10 @@ data class TimingSettings(
 
         /** Default anchor position: 42% from left (left of center) */
         const val DEFAULT_ANCHOR_POSITION = 0.42f
-        /** Default split chunk multiplier: 30% extra time */
+        /**
+         * Default split chunk multiplier: 30% extra time.
+         * SYNC: Must match SPLIT_CHUNK_MULTIPLIER in rust/src/types.rs
+         */
         const val DEFAULT_SPLIT_CHUNK_MULTIPLIER = 1.3f
         /** Default vertical position in portrait: upper quarter */
         const val DEFAULT_VERTICAL_PORTRAIT = 0.22f
@@ -91,6 +95,7 @@ data class TimingSettings(
             maxDisplayChars = DEFAULT_MAX_DISPLAY_CHARS
         )
 
+        // SYNC: Delays must match PUNCT_PAUSE_MS in rust/src/types.rs
         val Natural = TimingSettings(
             baseWpm = 300,
             periodDelayMs = 150,
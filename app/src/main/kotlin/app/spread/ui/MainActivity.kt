@@ -123,7 +123,7 @@ private suspend fun loadEpubFromUri(
         val bytes = inputStream.use { it.readBytes() }
         if (bytes.isEmpty()) return@withContext Pair(null, "File is empty")
 
-        val nativeBook = NativeParser.parseEpubWithConfig(bytes, maxChunkChars)
+        val nativeBook = NativeParser.parseEpubFlat(bytes, maxChunkChars)
             ?: return@withContext Pair(null, "Parser returned null (${bytes.size} bytes)")
 
         // Generate a unique ID for this book
@@ -150,7 +150,7 @@ private suspend fun loadDemoBook(
     try {
         val bytes = context.assets.open("demo.epub").use { it.readBytes() }
 
-        val nativeBook = NativeParser.parseEpubWithConfig(bytes, maxChunkChars)
+        val nativeBook = NativeParser.parseEpubFlat(bytes, maxChunkChars)
             ?: return@withContext null
 
         val bookId = "demo-book"
@@ -73,7 +73,7 @@ class ReaderViewModel(application: Application) : AndroidViewModel(application)
         reparseJob?.cancel()
         reparseJob = viewModelScope.launch(Dispatchers.IO) {
             val source = effect.source
-            val newBook = NativeParser.parseEpubWithConfig(source.bytes, effect.maxChunkChars)
+            val newBook = NativeParser.parseEpubFlat(source.bytes, effect.maxChunkChars)
                 ?.toDomain(source.bookId)
 
             if (newBook != null) {
@@ -0,0 +1,42 @@
+//! `WordArena` against `Vec<Word>`: `cargo bench --features arena --bench arena`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use spread_core::arena::WordArena;
+use spread_core::{parse_epub, Word};
+use std::time::Duration;
+
+fn bench_word_storage(c: &mut Criterion) {
+    let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pride-and-prejudice.epub");
+    let data = std::fs::read(epub_path)
+        .expect("Test fixture not found - run: cp pride-and-prejudice.epub rust/tests/fixtures/");
+    let book = parse_epub(&data).expect("Parse failed");
+    let words: Vec<&Word> = book.chapters.iter().flat_map(|c| &c.words).collect();
+
+    let mut group = c.benchmark_group("word_storage");
+    group.bench_function("vec_of_words", |b| {
+        b.iter(|| {
+            let mut stored = Vec::new();
+            for word in &words {
+                stored.push((*word).clone());
+            }
+            black_box(stored)
+        })
+    });
+    group.bench_function("word_arena", |b| {
+        b.iter(|| {
+            let mut arena = WordArena::new();
+            for word in &words {
+                arena.push_word(&word.text, word.length_bucket, word.following_punct);
+            }
+            black_box(arena)
+        })
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().measurement_time(Duration::from_secs(5)).sample_size(50);
+    targets = bench_word_storage
+}
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Parsing and tokenizing benchmarks: `cargo bench --bench parse_epub`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use spread_core::tokenizer;
+use spread_core::{parse_epub, parse_epub_with_config, read_epub_metadata, ParseConfig};
+use std::time::Duration;
+
+fn config(max_chunk_chars: usize) -> ParseConfig {
+    ParseConfig {
+        max_chunk_chars,
+        ..Default::default()
+    }
+}
+
+fn fixture() -> Vec<u8> {
+    let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pride-and-prejudice.epub");
+    std::fs::read(epub_path).expect("Test fixture not found - run: cp pride-and-prejudice.epub rust/tests/fixtures/")
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let data = fixture();
+
+    c.bench_function("parse_epub", |b| b.iter(|| parse_epub(black_box(&data)).expect("Parse failed")));
+
+    let mut group = c.benchmark_group("parse_epub_with_config");
+    for max_chunk_chars in [10, 15, 22] {
+        group.bench_with_input(BenchmarkId::from_parameter(max_chunk_chars), &config(max_chunk_chars), |b, config| {
+            b.iter(|| parse_epub_with_config(black_box(&data), config).expect("Parse failed"))
+        });
+    }
+    group.finish();
+}
+
+/// The metadata fast path against a full parse of the same book
+fn bench_metadata(c: &mut Criterion) {
+    let data = fixture();
+
+    let mut group = c.benchmark_group("metadata");
+    group.bench_function("read_epub_metadata", |b| {
+        b.iter(|| read_epub_metadata(black_box(&data)).expect("Metadata read failed"))
+    });
+    group.bench_function("parse_epub", |b| b.iter(|| parse_epub(black_box(&data)).expect("Parse failed")));
+    group.finish();
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    let sentence = "It is a truth universally acknowledged, that a single man in possession \
+                    of a good fortune, must be in want of a wife. ";
+    let sentence_words = sentence.split_whitespace().count();
+    let text = sentence.repeat(10_000_usize.div_ceil(sentence_words));
+
+    c.bench_function("tokenize/10k_words", |b| b.iter(|| tokenizer::tokenize(black_box(&text))));
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().measurement_time(Duration::from_secs(5)).sample_size(50);
+    targets = bench_parse, bench_metadata, bench_tokenize
+}
+criterion_main!(benches);
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(book) = spread_core::parse_epub(data) {
+        let words: usize = book.chapters.iter().map(|c| c.words.len()).sum();
+        assert_eq!(book.stats.total_words as usize, words);
+    }
+});
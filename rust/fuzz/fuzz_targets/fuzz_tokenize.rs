@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    let words = spread_core::tokenizer::tokenize(text);
+    assert!(words.iter().all(|w| !w.text.is_empty()));
+});
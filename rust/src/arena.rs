@@ -0,0 +1,122 @@
+//! Compact word storage (feature `arena`).
+//!
+//! Every `Word` owns a `String`, so a 100,000-word book makes 100,000 small
+//! heap allocations. `WordArena` keeps all word texts in one contiguous pool
+//! and each word as a fixed-size `ArenaWord`, so a whole chapter costs two
+//! growable buffers.
+
+use crate::types::{LengthBucket, Punctuation, Word};
+
+/// A word stored in a `WordArena`: its text's byte range in the pool plus
+/// the timing metadata from `Word`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArenaWord {
+    pub text_start: u32,
+    pub text_len: u16,
+    pub length_bucket: LengthBucket,
+    pub following_punct: Punctuation,
+}
+
+/// Word texts in one string pool, with an `ArenaWord` per word.
+#[derive(Debug, Clone, Default)]
+pub struct WordArena {
+    pool: String,
+    words: Vec<ArenaWord>,
+}
+
+impl WordArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Room for `words` words totalling `text_bytes` bytes of text.
+    pub fn with_capacity(words: usize, text_bytes: usize) -> Self {
+        WordArena {
+            pool: String::with_capacity(text_bytes),
+            words: Vec::with_capacity(words),
+        }
+    }
+
+    /// Texts and metadata of `words`, in order. Chunk roles are not kept.
+    pub fn from_words(words: &[Word]) -> Self {
+        let text_bytes = words.iter().map(|w| w.text.len()).sum();
+        let mut arena = Self::with_capacity(words.len(), text_bytes);
+        for word in words {
+            arena.push_word(&word.text, word.length_bucket, word.following_punct);
+        }
+        arena
+    }
+
+    /// Append a word and return its index.
+    ///
+    /// Text longer than `u16::MAX` bytes is cut at the last char boundary
+    /// that fits. Panics if the pool would exceed `u32::MAX` bytes.
+    pub fn push_word(&mut self, text: &str, bucket: LengthBucket, punct: Punctuation) -> usize {
+        let mut len = text.len().min(u16::MAX as usize);
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+        assert!(self.pool.len() + len <= u32::MAX as usize, "WordArena text pool exceeds u32::MAX bytes");
+        let text_start = self.pool.len() as u32;
+
+        self.pool.push_str(&text[..len]);
+        self.words.push(ArenaWord {
+            text_start,
+            text_len: len as u16,
+            length_bucket: bucket,
+            following_punct: punct,
+        });
+        self.words.len() - 1
+    }
+
+    /// Text of the word at `index`. Panics if `index` is out of range.
+    pub fn get(&self, index: usize) -> &str {
+        let word = &self.words[index];
+        let start = word.text_start as usize;
+        &self.pool[start..start + word.text_len as usize]
+    }
+
+    /// Metadata of the word at `index`, or `None` if out of range.
+    pub fn word(&self, index: usize) -> Option<ArenaWord> {
+        self.words.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_matches_words() {
+        let words = crate::tokenizer::tokenize("Café au lait, s'il vous plaît!");
+        let arena = WordArena::from_words(&words);
+        assert_eq!(arena.len(), words.len());
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(arena.get(i), word.text);
+            let stored = arena.word(i).unwrap();
+            assert_eq!(stored.length_bucket, word.length_bucket);
+            assert_eq!(stored.following_punct, word.following_punct);
+        }
+        assert!(arena.word(words.len()).is_none());
+    }
+
+    #[test]
+    fn test_arena_truncates_at_char_boundary() {
+        let mut arena = WordArena::new();
+        let long = "é".repeat(40_000); // 80,000 bytes
+        let index = arena.push_word(&long, LengthBucket::VeryLong, Punctuation::None);
+        assert_eq!(arena.get(index).len(), u16::MAX as usize - 1);
+        assert!(arena.get(index).chars().all(|c| c == 'é'));
+
+        let next = arena.push_word("next", LengthBucket::Short, Punctuation::Period);
+        assert_eq!(arena.get(next), "next");
+    }
+}
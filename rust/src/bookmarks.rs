@@ -0,0 +1,186 @@
+//! Saved reading positions and their EPUB CFI form.
+//!
+//! A CFI (Canonical Fragment Identifier) lets a position be exchanged with
+//! other readers and sync services. Only the subset needed to point at a
+//! word in a chapter is generated and accepted here: the spine step and
+//! `[manifest-id]` assertion locate the chapter in any reader, while the
+//! offset is a word index into `Chapter::words`, so it only lands on the
+//! same word when the book is parsed with the same chunk settings.
+
+use crate::types::Book;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Errors from `Bookmark::from_epub_cfi`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CfiError {
+    #[error("Malformed CFI: {0}")]
+    Malformed(String),
+    #[error("No chapter at spine index {0}")]
+    ChapterNotFound(usize),
+    #[error("Word offset {0} is past the end of the chapter")]
+    WordOutOfRange(usize),
+}
+
+/// A saved position in a book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    /// Position in `Book::chapters`
+    pub chapter_index: usize,
+    /// Index into that chapter's `words`
+    pub word_index: usize,
+    pub note: Option<String>,
+    /// Creation time, seconds since the Unix epoch
+    pub created_at: u64,
+}
+
+impl Bookmark {
+    /// A bookmark without a note, created now.
+    pub fn new(chapter_index: usize, word_index: usize) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Bookmark {
+            chapter_index,
+            word_index,
+            note: None,
+            created_at,
+        }
+    }
+
+    /// EPUB CFI for this position: `epubcfi(/6/N[id]!/4/1:W)`.
+    ///
+    /// `N` is the even step of the chapter's spine item (`(index + 1) * 2`),
+    /// `id` its `Chapter::manifest_id` (the assertion is left out for
+    /// chapters without one) and `W` the word index.
+    ///
+    /// `None` if the position isn't in `book`.
+    pub fn to_epub_cfi(&self, book: &Book) -> Option<String> {
+        let chapter = book.chapters.get(self.chapter_index)?;
+        if self.word_index >= chapter.words.len() {
+            return None;
+        }
+        let assertion = chapter.manifest_id.as_ref().map_or(String::new(), |id| format!("[{}]", id));
+        Some(format!("epubcfi(/6/{}{}!/4/1:{})", (chapter.index as usize + 1) * 2, assertion, self.word_index))
+    }
+
+    /// Parse a CFI made by `to_epub_cfi` back into a bookmark for `book`,
+    /// e.g. after the book was re-parsed. The `epubcfi(...)` wrapper and id
+    /// assertions are optional; a spine item's `[id]` assertion takes
+    /// precedence over its step when a chapter has that manifest id.
+    ///
+    /// The CFI only holds the position: the bookmark has no `note` and is
+    /// stamped with the current time, so copy those over from the original
+    /// bookmark if it's still around.
+    pub fn from_epub_cfi(cfi: &str, book: &Book) -> Result<Bookmark, CfiError> {
+        let malformed = || CfiError::Malformed(cfi.to_string());
+        let path = cfi
+            .strip_prefix("epubcfi(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .unwrap_or(cfi);
+
+        let (spine_path, content_path) = path.split_once('!').ok_or_else(malformed)?;
+        let steps: Vec<(usize, Option<&str>)> = spine_path
+            .split('/')
+            .skip(1)
+            .map(|step| cfi_step(step).ok_or_else(malformed))
+            .collect::<Result<_, _>>()?;
+        let [(6, _), (item_step, id)] = steps[..] else {
+            return Err(malformed());
+        };
+        if item_step == 0 || item_step % 2 != 0 {
+            return Err(malformed());
+        }
+        let spine_index = item_step / 2 - 1;
+
+        let (_, offset) = content_path.rsplit_once(':').ok_or_else(malformed)?;
+        let word_index: usize = offset.parse().map_err(|_| malformed())?;
+
+        let by_id = id.and_then(|id| book.chapters.iter().position(|c| c.manifest_id.as_deref() == Some(id)));
+        let chapter_index = by_id
+            .or_else(|| book.chapters.iter().position(|c| c.index as usize == spine_index))
+            .ok_or(CfiError::ChapterNotFound(spine_index))?;
+        if word_index >= book.chapters[chapter_index].words.len() {
+            return Err(CfiError::WordOutOfRange(word_index));
+        }
+        Ok(Bookmark::new(chapter_index, word_index))
+    }
+}
+
+/// Numeric value of one CFI path step and its `[id]` assertion, if any.
+fn cfi_step(step: &str) -> Option<(usize, Option<&str>)> {
+    match step.split_once('[') {
+        Some((number, assertion)) => Some((number.parse().ok()?, Some(assertion.strip_suffix(']')?))),
+        None => Some((step.parse().ok()?, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::create_chapter;
+    use crate::types::{BookMetadata, BookStats, EpubGuide};
+
+    fn book() -> Book {
+        // Spine item 0 (a cover) was skipped, so chapters start at spine index 1
+        let mut chapters = vec![
+            create_chapter(1, "One".to_string(), &["First chapter text."]),
+            create_chapter(2, "Two".to_string(), &["Second chapter has more words."]),
+        ];
+        chapters[1].manifest_id = Some("chap02".to_string());
+        let stats = BookStats::from_chapters(&chapters);
+        Book {
+            metadata: BookMetadata::default(),
+            guide: EpubGuide::default(),
+            chapters,
+            stats,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_cfi_round_trip() {
+        let book = book();
+        let bookmark = Bookmark::new(1, 3);
+        let cfi = bookmark.to_epub_cfi(&book).unwrap();
+        assert_eq!(cfi, "epubcfi(/6/6[chap02]!/4/1:3)");
+
+        let restored = Bookmark::from_epub_cfi(&cfi, &book).unwrap();
+        assert_eq!((restored.chapter_index, restored.word_index), (1, 3));
+        assert!(restored.created_at > 0);
+        assert_eq!(restored.note, None);
+
+        assert_eq!(Bookmark::new(0, 2).to_epub_cfi(&book).as_deref(), Some("epubcfi(/6/4!/4/1:2)"));
+        let without_id = Bookmark::from_epub_cfi("/6/4!/4/1:2", &book).unwrap();
+        assert_eq!((without_id.chapter_index, without_id.word_index), (0, 2));
+        // The id assertion wins over a stale spine step
+        let moved = Bookmark::from_epub_cfi("/6/4[chap02]!/4/1:4", &book).unwrap();
+        assert_eq!((moved.chapter_index, moved.word_index), (1, 4));
+
+        assert_eq!(Bookmark::new(2, 0).to_epub_cfi(&book), None);
+        assert_eq!(Bookmark::new(0, 3).to_epub_cfi(&book), None);
+    }
+
+    #[test]
+    fn test_cfi_errors() {
+        let book = book();
+        assert!(matches!(Bookmark::from_epub_cfi("epubcfi(/6/4)", &book), Err(CfiError::Malformed(_))));
+        assert!(matches!(Bookmark::from_epub_cfi("/6/5!/4/1:0", &book), Err(CfiError::Malformed(_))));
+        assert!(matches!(Bookmark::from_epub_cfi("/6/4!/4/1:x", &book), Err(CfiError::Malformed(_))));
+        assert!(matches!(Bookmark::from_epub_cfi("/6/4[chap01!/4/1:0", &book), Err(CfiError::Malformed(_))));
+        assert_eq!(Bookmark::from_epub_cfi("/6/2!/4/1:0", &book), Err(CfiError::ChapterNotFound(0)));
+        assert_eq!(Bookmark::from_epub_cfi("/6/4!/4/1:3", &book), Err(CfiError::WordOutOfRange(3)));
+    }
+
+    #[test]
+    fn test_cfi_from_parsed_epub() {
+        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(epub_path).expect("Failed to read demo.epub");
+        let book = crate::epub::parse_epub(&data).unwrap();
+        let id = book.chapters[0].manifest_id.clone().expect("spine chapters have a manifest id");
+
+        let cfi = Bookmark::new(0, 0).to_epub_cfi(&book).unwrap();
+        assert!(cfi.contains(&format!("[{}]!", id)), "{}", cfi);
+        assert_eq!(Bookmark::from_epub_cfi(&cfi, &book).unwrap().chapter_index, 0);
+    }
+}
@@ -5,12 +5,20 @@
 //! - content.opf (or similar) -> metadata + spine (reading order) + manifest (file list)
 //! - XHTML files -> actual chapter content
 
-use crate::tokenizer::{create_chapter_with_config, DEFAULT_MAX_CHUNK_CHARS};
-use crate::types::{Book, BookMetadata, BookStats};
+use crate::tokenizer::{
+    create_chapter_with_config, extract_text_from_html, is_note_type, TokenizeConfig, DEFAULT_MAX_CHUNK_CHARS,
+    DEFAULT_MIN_CHUNK_CHARS,
+};
+use crate::types::{
+    split_leading_article, Book, BookMetadata, BookStats, Chapter, ChunkRole, ChapterStats, EpubGuide, EpubVersion, Footnote,
+    ParseWarning, Word,
+};
 use quick_xml::events::Event;
-use quick_xml::Reader;
-use std::collections::HashMap;
-use std::io::{Cursor, Read};
+use quick_xml::{NsReader, Reader};
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
 use zip::ZipArchive;
 
@@ -28,214 +36,1226 @@ pub enum EpubError {
     MissingOpf,
     #[error("Invalid EPUB structure: {0}")]
     InvalidStructure(String),
+    #[error("Parse warning treated as error: {0}")]
+    Warning(ParseWarning),
+    #[error("EPUB content too large: {0} bytes uncompressed")]
+    TooLarge(u64),
+    #[error("Suspicious compression ratio: {0:.0}:1")]
+    SuspiciousCompression(f64),
+    #[error("EPUB is password-protected")]
+    Encrypted,
 }
 
-/// Parse an EPUB file from bytes with configurable chunk size.
+impl EpubError {
+    /// Stable machine-readable code, e.g. `"epub/missing-container"`, for
+    /// mapping to a localized message. Unlike `Display`, these never change.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            EpubError::Zip(_) => "epub/zip-error",
+            EpubError::Io(_) => "epub/io-error",
+            EpubError::Xml(_) => "epub/xml-error",
+            EpubError::MissingContainer => "epub/missing-container",
+            EpubError::MissingOpf => "epub/missing-opf",
+            EpubError::InvalidStructure(_) => "epub/invalid-structure",
+            EpubError::Warning(_) => "epub/warning",
+            EpubError::TooLarge(_) => "epub/too-large",
+            EpubError::SuspiciousCompression(_) => "epub/suspicious-compression",
+            EpubError::Encrypted => "epub/encrypted",
+        }
+    }
+
+    /// True if a retry could still produce a (partial) book: the structure
+    /// was off but the archive is readable, or a warning was promoted to an
+    /// error by `ParseConfig::treat_warnings_as_errors`. Unreadable or
+    /// password-protected archives and rejected zip bombs are not recoverable.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, EpubError::InvalidStructure(_) | EpubError::Warning(_))
+    }
+}
+
+/// Default `ParseConfig::max_uncompressed_size`: 256 MB of XHTML.
+pub const DEFAULT_MAX_UNCOMPRESSED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Default `ParseConfig::max_xhtml_bytes`: 50 MB for a single file.
+pub const DEFAULT_MAX_XHTML_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Default `ParseConfig::max_compression_ratio`. Real EPUBs compress text
+/// around 3:1; zip bombs reach 1000:1 and more.
+pub const DEFAULT_MAX_COMPRESSION_RATIO: f64 = 100.0;
+
+/// Word count below which `ParseConfig::merge_sequential_short_chapters`
+/// merges a chapter into the next one.
+pub const SHORT_CHAPTER_WORDS: u32 = 100;
+
+/// Predicate for `ParseConfig::content_filter`; `false` drops the chapter.
+pub type ChapterFilter = Arc<dyn Fn(&Chapter) -> bool + Send + Sync>;
+
+/// Options for `parse_epub_with_config`.
+#[derive(Clone)]
+pub struct ParseConfig {
+    /// Max letters per chunk (default 10, range 10-22).
+    /// maxDisplayChars from settings should be converted: max_chunk_chars = maxDisplayChars - 2
+    pub max_chunk_chars: usize,
+    /// Min letters left after splitting off a prefix or suffix (default 3).
+    pub min_chunk_chars: usize,
+    /// Fail with `EpubError::Warning` on the first non-fatal issue instead of
+    /// collecting it in `Book::warnings`.
+    pub treat_warnings_as_errors: bool,
+    /// Read EPUB 3 Media Overlay (SMIL) files to fill `Chapter::audio_duration_ms`.
+    pub extract_audio_metadata: bool,
+    /// Skip spine items before the guide's `text` reference (cover, title
+    /// page, ...). Has no effect when the OPF has no such reference.
+    /// `LazyBook` reports these as `ParseWarning::FrontMatterSkipped`.
+    pub skip_front_matter: bool,
+    /// Merge each chapter with fewer than `SHORT_CHAPTER_WORDS` words into
+    /// the one after it, for tools that split a chapter across several
+    /// files. The merged chapter keeps the first file's title and index.
+    /// `LazyBook` loads single spine items and ignores this.
+    pub merge_sequential_short_chapters: bool,
+    /// Don't extract text from fixed-layout spine items (comic and picture
+    /// book pages, mostly images); each is reported as
+    /// `ParseWarning::FixedLayoutSkipped` instead.
+    pub skip_fixed_layout: bool,
+    /// Reject EPUBs whose XHTML entries declare more than this many
+    /// uncompressed bytes in total (`EpubError::TooLarge`).
+    pub max_uncompressed_size: u64,
+    /// Reject EPUBs with a single XHTML file larger than this, uncompressed
+    /// (`EpubError::TooLarge`). Raise it for books that are one giant HTML
+    /// file. Other files (OPF, NCX, ...) are held to the default.
+    pub max_xhtml_bytes: u64,
+    /// Reject EPUBs whose overall uncompressed:compressed ratio exceeds this
+    /// (`EpubError::SuspiciousCompression`).
+    pub max_compression_ratio: f64,
+    /// Called with each tokenized chapter; chapters it rejects are left out
+    /// of `Book::chapters` and `Book::stats`, e.g. "About the Author" or
+    /// copyright pages. Runs before `merge_sequential_short_chapters`.
+    /// `LazyBook` ignores this.
+    pub content_filter: Option<ChapterFilter>,
+    /// Which rendition to read when `container.xml` lists several.
+    pub rendition_selector: RenditionSelector,
+    /// Skip spine items whose table of contents entry is nested deeper than this
+    /// (`Chapter::nesting_level`), e.g. 0 to keep only top-level chapters.
+    /// Default `u8::MAX` keeps everything. `LazyBook` ignores this.
+    pub max_chapter_depth: u8,
+}
+
+/// Picks one of the `<rootfile>`s in `container.xml`. EPUB 3 allows several
+/// renditions of a book (e.g. reflowable and fixed-layout) in one package,
+/// each with its own OPF; readers default to the first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum RenditionSelector {
+    #[default]
+    First,
+    Last,
+    /// The first rootfile whose `media-type` attribute equals this, or the
+    /// first rootfile if none does.
+    ByMediaType(String),
+}
+
+impl RenditionSelector {
+    fn select<'a>(&self, rootfiles: &'a [Rootfile]) -> Option<&'a Rootfile> {
+        match self {
+            RenditionSelector::First => rootfiles.first(),
+            RenditionSelector::Last => rootfiles.last(),
+            RenditionSelector::ByMediaType(media_type) => rootfiles
+                .iter()
+                .find(|rootfile| rootfile.media_type == *media_type)
+                .or(rootfiles.first()),
+        }
+    }
+}
+
+impl std::fmt::Debug for ParseConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseConfig")
+            .field("max_chunk_chars", &self.max_chunk_chars)
+            .field("min_chunk_chars", &self.min_chunk_chars)
+            .field("treat_warnings_as_errors", &self.treat_warnings_as_errors)
+            .field("extract_audio_metadata", &self.extract_audio_metadata)
+            .field("skip_front_matter", &self.skip_front_matter)
+            .field("merge_sequential_short_chapters", &self.merge_sequential_short_chapters)
+            .field("skip_fixed_layout", &self.skip_fixed_layout)
+            .field("max_uncompressed_size", &self.max_uncompressed_size)
+            .field("max_xhtml_bytes", &self.max_xhtml_bytes)
+            .field("max_compression_ratio", &self.max_compression_ratio)
+            .field("content_filter", &self.content_filter.as_ref().map(|_| "<fn>"))
+            .field("rendition_selector", &self.rendition_selector)
+            .field("max_chapter_depth", &self.max_chapter_depth)
+            .finish()
+    }
+}
+
+/// The settings `parse_epub` uses: `parse_epub_with_config(data,
+/// &ParseConfig::default())` gives exactly the same `Book` as
+/// `parse_epub(data)`, so switching to the config API changes nothing until
+/// a field is set.
+impl Default for ParseConfig {
+    fn default() -> Self {
+        ParseConfig {
+            max_chunk_chars: DEFAULT_MAX_CHUNK_CHARS,
+            min_chunk_chars: DEFAULT_MIN_CHUNK_CHARS,
+            treat_warnings_as_errors: false,
+            extract_audio_metadata: false,
+            skip_front_matter: false,
+            merge_sequential_short_chapters: false,
+            skip_fixed_layout: false,
+            max_uncompressed_size: DEFAULT_MAX_UNCOMPRESSED_SIZE,
+            max_xhtml_bytes: DEFAULT_MAX_XHTML_BYTES,
+            max_compression_ratio: DEFAULT_MAX_COMPRESSION_RATIO,
+            content_filter: None,
+            rendition_selector: RenditionSelector::First,
+            max_chapter_depth: u8::MAX,
+        }
+    }
+}
+
+impl ParseConfig {
+    /// Tokenizer settings derived from this config.
+    pub fn tokenize_config(&self) -> TokenizeConfig {
+        TokenizeConfig {
+            min_chunk_chars: self.min_chunk_chars,
+            ..TokenizeConfig::with_max_chunk_chars(self.max_chunk_chars)
+        }
+    }
+}
+
+/// Parse an EPUB file from bytes with the given config.
+pub fn parse_epub_with_config(data: &[u8], config: &ParseConfig) -> Result<Book, EpubError> {
+    parse_epub_with_progress(data, config, |_, _| {})
+}
+
+/// `parse_epub_with_config`, calling `progress(chapters_done, chapters_total)`
+/// after each spine item is processed (skipped items included), for a
+/// progress bar.
 ///
-/// `max_chunk_chars` controls max letters per chunk (default 10, range 10-22).
-/// maxDisplayChars from settings should be converted: max_chunk_chars = maxDisplayChars - 2
-pub fn parse_epub_with_config(data: &[u8], max_chunk_chars: usize) -> Result<Book, EpubError> {
+/// `chapters_total` is the spine length, known before any XHTML is read, so
+/// the last call is always `progress(total, total)`. The callback runs on the
+/// parsing thread and must not call back into `parse_epub*`.
+pub fn parse_epub_with_progress<F>(data: &[u8], config: &ParseConfig, mut progress: F) -> Result<Book, EpubError>
+where
+    F: FnMut(usize, usize),
+{
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor)?;
+    check_archive_size(&mut archive, config)?;
+    check_not_encrypted(&mut archive)?;
 
     // Step 1: Read container.xml to find OPF path
-    let opf_path = read_container(&mut archive)?;
+    let (opf_path, container_warning) = locate_opf(&mut archive, &config.rendition_selector)?;
 
     // Step 2: Parse OPF to get metadata and spine
-    let (metadata, spine, manifest) = read_opf(&mut archive, &opf_path)?;
+    let OpfPackage {
+        metadata,
+        spine,
+        manifest,
+        media_overlays,
+        ncx_href,
+        nav_href,
+        fixed_layout,
+        guide,
+        mut warnings,
+    } = read_opf(&mut archive, &opf_path)?;
+    warnings.splice(0..0, container_warning);
 
     // Step 3: Read and parse each chapter in spine order
     let opf_dir = opf_path
         .rsplit_once('/')
         .map(|(dir, _)| dir)
         .unwrap_or("");
+    let nav_points = read_nav_points(&mut archive, opf_dir, nav_href.as_deref(), ncx_href.as_deref(), &manifest);
 
-    let mut chapters = Vec::new();
-    for (index, item_id) in spine.iter().enumerate() {
-        if let Some(href) = manifest.get(item_id) {
-            let full_path = if opf_dir.is_empty() {
-                href.clone()
-            } else {
-                format!("{}/{}", opf_dir, href)
-            };
+    let first_item = if config.skip_front_matter {
+        text_start_index(&guide, &spine, &manifest).unwrap_or(0)
+    } else {
+        0
+    };
 
-            if let Ok(content) = read_file(&mut archive, &full_path) {
-                let text = extract_text_from_xhtml(&content);
-                let paragraphs: Vec<&str> = text
-                    .split("\n\n")
-                    .map(|s| s.trim())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-
-                if !paragraphs.is_empty() {
-                    let title = extract_title_from_xhtml(&content)
-                        .unwrap_or_else(|| format!("Chapter {}", index + 1));
-
-                    chapters.push(create_chapter_with_config(
-                        index as u32,
-                        title,
-                        &paragraphs,
-                        max_chunk_chars,
-                    ));
+    let mut chapters = Vec::new();
+    for (index, item_id) in spine.iter().enumerate().skip(first_item) {
+        if config.skip_fixed_layout && fixed_layout.contains(&index) {
+            warnings.push(ParseWarning::FixedLayoutSkipped(index));
+            progress(index + 1, spine.len());
+            continue;
+        }
+        let nav_point = nav_points.get(item_id);
+        if nav_point.is_some_and(|point| point.nesting_level > config.max_chapter_depth) {
+            progress(index + 1, spine.len());
+            continue;
+        }
+        let item = SpineItem { index, id: item_id, nav_point };
+        match load_spine_item(&mut archive, opf_dir, &manifest, item, config, &mut warnings) {
+            Ok(mut chapter) => {
+                if config.extract_audio_metadata {
+                    chapter.audio_duration_ms = load_audio_duration(&mut archive, opf_dir, &media_overlays, item_id);
+                }
+                if config.content_filter.as_ref().is_none_or(|keep| keep(&chapter)) {
+                    chapters.push(chapter)
                 }
             }
+            Err(warning) => warnings.push(warning),
         }
+        progress(index + 1, spine.len());
+    }
+    if config.merge_sequential_short_chapters {
+        chapters = merge_short_chapters(chapters);
+    }
+
+    // Missing spine files are skipped, unless none of the book is left
+    if chapters.is_empty() && warnings.iter().any(|w| matches!(w, ParseWarning::MissingSpineItem { .. })) {
+        return Err(EpubError::InvalidStructure("No readable spine items".to_string()));
+    }
+
+    if config.treat_warnings_as_errors {
+        if let Some(warning) = warnings.into_iter().next() {
+            return Err(EpubError::Warning(warning));
+        }
+        warnings = Vec::new();
     }
 
     let stats = BookStats::from_chapters(&chapters);
+    for (chapter, &start) in chapters.iter_mut().zip(&stats.chapter_offsets) {
+        chapter.global_word_start = start;
+    }
 
     Ok(Book {
         metadata,
+        guide,
         chapters,
         stats,
+        warnings,
     })
 }
 
+/// Parse an EPUB file from disk with default chunk size.
+pub fn parse_epub_from_path(path: impl AsRef<Path>) -> Result<Book, EpubError> {
+    parse_epub(&std::fs::read(path)?)
+}
+
+/// Parse an EPUB file from disk with the given config.
+pub fn parse_epub_from_path_with_config(
+    path: impl AsRef<Path>,
+    config: &ParseConfig,
+) -> Result<Book, EpubError> {
+    parse_epub_with_config(&std::fs::read(path)?, config)
+}
+
+/// Parse an EPUB from any reader with default chunk size.
+/// The whole stream is buffered, since ZIP needs random access.
+pub fn parse_epub_from_reader<R: Read>(reader: R) -> Result<Book, EpubError> {
+    parse_epub(&read_all(reader)?)
+}
+
+/// Parse an EPUB from any reader with the given config.
+pub fn parse_epub_from_reader_with_config<R: Read>(
+    reader: R,
+    config: &ParseConfig,
+) -> Result<Book, EpubError> {
+    parse_epub_with_config(&read_all(reader)?, config)
+}
+
+fn read_all<R: Read>(mut reader: R) -> Result<Vec<u8>, EpubError> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Parse on tokio's blocking thread pool so async callers aren't stalled.
+/// Takes the bytes by value because the blocking task must be `'static`.
+/// A panic inside the parser is resumed on the awaiting task.
+#[cfg(feature = "tokio")]
+pub async fn parse_epub_async(data: Vec<u8>) -> Result<Book, EpubError> {
+    join_blocking(tokio::task::spawn_blocking(move || parse_epub(&data))).await
+}
+
+/// Async `parse_epub_with_config`; see `parse_epub_async`.
+#[cfg(feature = "tokio")]
+pub async fn parse_epub_with_config_async(data: Vec<u8>, config: ParseConfig) -> Result<Book, EpubError> {
+    join_blocking(tokio::task::spawn_blocking(move || parse_epub_with_config(&data, &config))).await
+}
+
+#[cfg(feature = "tokio")]
+async fn join_blocking<T>(handle: tokio::task::JoinHandle<T>) -> T {
+    match handle.await {
+        Ok(value) => value,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Tokenize loose (X)HTML files, e.g. from an unzipped EPUB, as chapters.
+///
+/// `files` are `(filename, bytes)` pairs and are read in filename order, so
+/// name them in reading order ("01.xhtml", "02.xhtml", ...). Malformed XML
+/// falls back to the lenient HTML extractor as in `parse_epub`; files
+/// without text are skipped, and `Chapter::index` is the position in the
+/// sorted list. Fails with `InvalidStructure` if no file has any text.
+pub fn parse_chapters_from_html_bytes(files: &[(&str, &[u8])]) -> Result<Vec<Chapter>, EpubError> {
+    let mut files = files.to_vec();
+    files.sort_by_key(|&(name, _)| name);
+
+    let config = TokenizeConfig::default();
+    let mut warnings = Vec::new();
+    let mut chapters: Vec<Chapter> = files
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (name, content))| chapter_from_xhtml(content, name, index, None, &config, &mut warnings).ok())
+        .collect();
+    if chapters.is_empty() {
+        return Err(EpubError::InvalidStructure("No readable HTML files".to_string()));
+    }
+
+    let mut global_word_start = 0;
+    for chapter in &mut chapters {
+        chapter.global_word_start = global_word_start;
+        global_word_start += chapter.stats.word_count;
+    }
+    Ok(chapters)
+}
+
+/// Read only the book metadata, without touching any XHTML content.
+///
+/// Opens the ZIP, reads `META-INF/container.xml` and the OPF, and stops there.
+/// Use this for library listings where the tokenized text isn't needed.
+pub fn read_epub_metadata(data: &[u8]) -> Result<BookMetadata, EpubError> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let (opf_path, _) = locate_opf(&mut archive, &RenditionSelector::First)?;
+    Ok(read_opf(&mut archive, &opf_path)?.metadata)
+}
+
+/// Write `book` back out as a minimal EPUB 2: `container.xml`, an OPF with
+/// the metadata and spine, an NCX with the chapter titles, and one XHTML
+/// file per chapter with a `<p>` per paragraph.
+///
+/// Only the text survives; footnotes, images, styling and audio are
+/// dropped. Split words are rejoined, so `parse_epub` on the result gives
+/// the same chapters, titles and word count. A chapter whose first
+/// paragraph is its title (the heading the title came from) gets that
+/// paragraph as its `<h1>`; otherwise the title is only in the NCX, so no
+/// words are added.
+pub fn export_epub(book: &Book) -> Result<Vec<u8>, EpubError> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let deflated = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    // The mimetype must come first and be stored uncompressed
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(EXPORT_CONTAINER_XML.as_bytes())?;
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(export_opf(book).as_bytes())?;
+    zip.start_file("OEBPS/toc.ncx", deflated)?;
+    zip.write_all(export_ncx(book).as_bytes())?;
+    for (i, chapter) in book.chapters.iter().enumerate() {
+        zip.start_file(format!("OEBPS/{}", export_chapter_href(i)), deflated)?;
+        zip.write_all(export_chapter_xhtml(chapter).as_bytes())?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+const EXPORT_CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+fn export_chapter_href(position: usize) -> String {
+    format!("chapter{:03}.xhtml", position + 1)
+}
+
+fn export_opf(book: &Book) -> String {
+    use quick_xml::escape::escape;
+
+    let metadata = &book.metadata;
+    let mut opf = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"2.0\" unique-identifier=\"bookid\">\n  \
+         <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:opf=\"http://www.idpf.org/2007/opf\">\n",
+    );
+    opf.push_str(&format!("    <dc:title>{}</dc:title>\n", escape(&metadata.title)));
+    for author in metadata.authors() {
+        opf.push_str(&format!("    <dc:creator opf:role=\"aut\">{}</dc:creator>\n", escape(author)));
+    }
+    opf.push_str("    <dc:language>und</dc:language>\n");
+    opf.push_str("    <dc:identifier id=\"bookid\">spread-export</dc:identifier>\n");
+    if let Some(series) = &metadata.series {
+        opf.push_str(&format!("    <meta name=\"calibre:series\" content=\"{}\"/>\n", escape(series)));
+        if let Some(index) = metadata.series_index {
+            opf.push_str(&format!("    <meta name=\"calibre:series_index\" content=\"{}\"/>\n", index));
+        }
+    }
+    opf.push_str("  </metadata>\n  <manifest>\n");
+    opf.push_str("    <item id=\"ncx\" href=\"toc.ncx\" media-type=\"application/x-dtbncx+xml\"/>\n");
+    for i in 0..book.chapters.len() {
+        opf.push_str(&format!(
+            "    <item id=\"c{}\" href=\"{}\" media-type=\"application/xhtml+xml\"/>\n",
+            i + 1,
+            export_chapter_href(i)
+        ));
+    }
+    opf.push_str("  </manifest>\n  <spine toc=\"ncx\">\n");
+    for i in 0..book.chapters.len() {
+        opf.push_str(&format!("    <itemref idref=\"c{}\"/>\n", i + 1));
+    }
+    opf.push_str("  </spine>\n</package>\n");
+    opf
+}
+
+fn export_ncx(book: &Book) -> String {
+    use quick_xml::escape::escape;
+
+    let mut ncx = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <ncx xmlns=\"http://www.daisy.org/z3986/2005/ncx/\" version=\"2005-1\">\n  \
+         <head><meta name=\"dtb:uid\" content=\"spread-export\"/></head>\n",
+    );
+    ncx.push_str(&format!("  <docTitle><text>{}</text></docTitle>\n  <navMap>\n", escape(&book.metadata.title)));
+    for (i, chapter) in book.chapters.iter().enumerate() {
+        ncx.push_str(&format!(
+            "    <navPoint id=\"n{0}\" playOrder=\"{0}\"><navLabel><text>{1}</text></navLabel><content src=\"{2}\"/></navPoint>\n",
+            i + 1,
+            escape(&chapter.title),
+            export_chapter_href(i)
+        ));
+    }
+    ncx.push_str("  </navMap>\n</ncx>\n");
+    ncx
+}
+
+fn export_chapter_xhtml(chapter: &Chapter) -> String {
+    use quick_xml::escape::escape;
+
+    let mut paragraphs = chapter.paragraphs.iter().map(|p| export_words_text(&chapter.words[p.word_range()])).filter(|t| !t.is_empty());
+    let mut body = String::new();
+    let mut first = paragraphs.next();
+    if first.as_deref() == Some(chapter.title.as_str()) {
+        body.push_str(&format!("  <h1>{}</h1>\n", escape(&chapter.title)));
+        first = None;
+    }
+    for text in first.into_iter().chain(paragraphs) {
+        body.push_str(&format!("  <p>{}</p>\n", escape(&text)));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <html xmlns=\"http://www.w3.org/1999/xhtml\">\n\
+         <head><title>{}</title></head>\n\
+         <body>\n{}</body>\n\
+         </html>\n",
+        escape(&chapter.title),
+        body
+    )
+}
+
+/// Words as source text: split chunks rejoined, pause words skipped.
+fn export_words_text(words: &[Word]) -> String {
+    let mut text = String::new();
+    for word in words.iter().filter(|w| !w.is_pause()) {
+        let continues_chunk = word.is_chunk && word.chunk_role != ChunkRole::First;
+        if !continues_chunk && !text.is_empty() {
+            text.push(' ');
+        }
+        if word.is_chunk {
+            text.push_str(word.text.trim_matches('-'));
+            if word.chunk_role == ChunkRole::Last {
+                text.extend(word.following_punct.as_char());
+            }
+        } else {
+            text.push_str(&word.text);
+        }
+    }
+    text
+}
+
+/// A spine entry to load
+struct SpineItem<'a> {
+    index: usize,
+    /// Manifest id
+    id: &'a str,
+    /// The item's table of contents entry, which supplies the chapter's
+    /// title and nesting level
+    nav_point: Option<&'a NavPoint>,
+}
+
+/// Read and tokenize a spine item.
+///
+/// Returns the reason as a warning when the item yields no chapter (missing
+/// from the manifest or ZIP, or no readable text). Recoverable XHTML problems
+/// are pushed to `warnings` and the chapter is still returned.
+fn load_spine_item<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    opf_dir: &str,
+    manifest: &HashMap<String, String>,
+    item: SpineItem,
+    config: &ParseConfig,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Chapter, ParseWarning> {
+    let SpineItem { index, id: item_id, nav_point } = item;
+    let Some(href) = manifest.get(item_id) else {
+        return Err(ParseWarning::MissingSpineItem {
+            item_id: item_id.to_string(),
+            href: String::new(),
+        });
+    };
+    let full_path = resolve_href(opf_dir, href);
+
+    let Ok(content) = read_file_with_limit(archive, &full_path, config.max_xhtml_bytes) else {
+        return Err(ParseWarning::MissingSpineItem {
+            item_id: item_id.to_string(),
+            href: full_path,
+        });
+    };
+    let title = nav_point.map(|point| point.title.as_str());
+    let mut chapter = chapter_from_xhtml(&content, &full_path, index, title, &config.tokenize_config(), warnings)?;
+    chapter.nesting_level = nav_point.map_or(0, |point| point.nesting_level);
+    chapter.manifest_id = Some(item_id.to_string());
+    Ok(chapter)
+}
+
+/// Tokenize one XHTML document into the chapter at `index`; `path` is only
+/// used in warnings. Errors as in `load_spine_item`.
+///
+/// `title_override` (a table of contents label) is used as the title when
+/// given; otherwise the title comes from the document's `<title>`/heading.
+fn chapter_from_xhtml(
+    content: &[u8],
+    path: &str,
+    index: usize,
+    title_override: Option<&str>,
+    config: &TokenizeConfig,
+    warnings: &mut Vec<ParseWarning>,
+) -> Result<Chapter, ParseWarning> {
+    // Fall back to the lenient HTML path when the content isn't well-formed XML
+    let (mut text, xml_error) = extract_text_from_xhtml(content);
+    if let Some(e) = xml_error {
+        warnings.push(ParseWarning::MalformedXhtml(format!("{}: {}", path, e)));
+        text = extract_text_from_html(content);
+    }
+    let paragraphs: Vec<&str> = text
+        .split("\n\n")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if paragraphs.is_empty() {
+        return Err(ParseWarning::EmptyChapter(index));
+    }
+
+    let title = match title_override {
+        Some(title) => title.to_string(),
+        None => extract_title_from_xhtml(content).unwrap_or_else(|| format!("Chapter {}", index + 1)),
+    };
+
+    let mut chapter = create_chapter_with_config(index as u32, title, &paragraphs, config);
+    chapter.footnotes = extract_footnotes(content);
+    Ok(chapter)
+}
+
+/// Fold every chapter under `SHORT_CHAPTER_WORDS` words into the next one.
+/// A short final chapter has nothing to merge into and is kept as is.
+fn merge_short_chapters(chapters: Vec<Chapter>) -> Vec<Chapter> {
+    let mut merged = Vec::with_capacity(chapters.len());
+    let mut pending: Option<Chapter> = None;
+
+    for chapter in chapters {
+        let chapter = match pending.take() {
+            Some(mut first) => {
+                append_chapter(&mut first, chapter);
+                first
+            }
+            None => chapter,
+        };
+        if chapter.stats.word_count < SHORT_CHAPTER_WORDS {
+            pending = Some(chapter);
+        } else {
+            merged.push(chapter);
+        }
+    }
+    merged.extend(pending);
+    merged
+}
+
+fn append_chapter(chapter: &mut Chapter, next: Chapter) {
+    let offset = chapter.words.len() as u32;
+    chapter.words.extend(next.words);
+    chapter.paragraphs.extend(next.paragraphs.into_iter().map(|mut paragraph| {
+        paragraph.word_start_offset += offset;
+        paragraph
+    }));
+    chapter.footnotes.extend(next.footnotes);
+    chapter.audio_duration_ms = match (chapter.audio_duration_ms, next.audio_duration_ms) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    };
+    chapter.stats = ChapterStats::from_words(&chapter.words);
+}
+
+/// Total narration length of the Media Overlay for a spine item, if it has one.
+/// A SMIL file that can't be read or parsed counts as no overlay.
+fn load_audio_duration<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    opf_dir: &str,
+    media_overlays: &HashMap<String, String>,
+    item_id: &str,
+) -> Option<u64> {
+    let href = media_overlays.get(item_id)?;
+    let full_path = resolve_href(opf_dir, href);
+    let content = read_file(archive, &full_path).ok()?;
+    smil_audio_duration_ms(&content)
+}
+
+/// Sum of `clipEnd - clipBegin` over every `<audio>` element in a SMIL file.
+/// Clips without a parseable `clipEnd` are skipped.
+fn smil_audio_duration_ms(content: &[u8]) -> Option<u64> {
+    let content_str = decode_xml(content);
+    let mut reader = Reader::from_str(&content_str);
+    let mut buf = Vec::new();
+    let mut total: u64 = 0;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if e.local_name().as_ref() == b"audio" => {
+                let mut begin = Some(0);
+                let mut end = None;
+                for attr in e.attributes().flatten() {
+                    let value = String::from_utf8_lossy(&attr.value);
+                    match attr.key.local_name().as_ref() {
+                        b"clipBegin" => begin = parse_clock_value_ms(&value),
+                        b"clipEnd" => end = parse_clock_value_ms(&value),
+                        _ => {}
+                    }
+                }
+                if let (Some(begin), Some(end)) = (begin, end) {
+                    total = total.saturating_add(end.saturating_sub(begin));
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(_) => return None,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Some(total)
+}
+
+/// Parse a SMIL clock value ("0:01:02.5", "02:03.25", "12.5s", "500ms",
+/// "1.5min", "2h", or bare seconds) into milliseconds.
+fn parse_clock_value_ms(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let seconds = if value.contains(':') {
+        // Full ("h:mm:ss.f") or partial ("mm:ss.f") clock value
+        let mut seconds = 0.0;
+        for part in value.split(':') {
+            seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+        }
+        seconds
+    } else {
+        let (number, scale) = if let Some(n) = value.strip_suffix("ms") {
+            (n, 0.001)
+        } else if let Some(n) = value.strip_suffix("min") {
+            (n, 60.0)
+        } else if let Some(n) = value.strip_suffix('h') {
+            (n, 3600.0)
+        } else if let Some(n) = value.strip_suffix('s') {
+            (n, 1.0)
+        } else {
+            (value, 1.0)
+        };
+        number.trim().parse::<f64>().ok()? * scale
+    };
+
+    (seconds.is_finite() && seconds >= 0.0).then(|| (seconds * 1000.0).round() as u64)
+}
+
 /// Parse an EPUB file from bytes with default chunk size.
 pub fn parse_epub(data: &[u8]) -> Result<Book, EpubError> {
-    parse_epub_with_config(data, DEFAULT_MAX_CHUNK_CHARS)
+    parse_epub_with_config(data, &ParseConfig::default())
+}
+
+/// `Book::try_from(data)` / `data.try_into()`, same as `parse_epub(data)`.
+impl TryFrom<&[u8]> for Book {
+    type Error = EpubError;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        parse_epub(data)
+    }
+}
+
+impl TryFrom<Vec<u8>> for Book {
+    type Error = EpubError;
+
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+        parse_epub(&data)
+    }
+}
+
+/// An EPUB whose chapters are decompressed and tokenized on demand.
+///
+/// Opening reads only the container and OPF; each spine item is parsed the
+/// first time it is requested and then cached. Chapter indices are spine
+/// indices, matching `Chapter::index` from `parse_epub`.
+///
+/// A chapter's global word offset depends on every chapter before it, so it
+/// is only known once those have been loaded; see `global_word_start`.
+pub struct LazyBook {
+    archive: ZipArchive<Cursor<Vec<u8>>>,
+    pub metadata: BookMetadata,
+    spine: Vec<String>,
+    manifest: HashMap<String, String>,
+    media_overlays: HashMap<String, String>,
+    nav_points: HashMap<String, NavPoint>,
+    fixed_layout: HashSet<usize>,
+    /// Spine index of the guide's `text` reference with
+    /// `ParseConfig::skip_front_matter`, else 0
+    text_start: usize,
+    opf_dir: String,
+    config: ParseConfig,
+    cache: HashMap<usize, Chapter>,
+    /// Spine indices tried and found to have no readable chapter
+    unreadable: HashSet<usize>,
+    warnings: Vec<ParseWarning>,
+}
+
+impl LazyBook {
+    /// Open an EPUB, taking ownership of its bytes.
+    pub fn open(data: Vec<u8>, config: ParseConfig) -> Result<Self, EpubError> {
+        let mut archive = ZipArchive::new(Cursor::new(data))?;
+        check_archive_size(&mut archive, &config)?;
+        check_not_encrypted(&mut archive)?;
+        let (opf_path, container_warning) = locate_opf(&mut archive, &config.rendition_selector)?;
+        let OpfPackage {
+            metadata,
+            spine,
+            manifest,
+            media_overlays,
+            ncx_href,
+            nav_href,
+            fixed_layout,
+            guide,
+            mut warnings,
+        } = read_opf(&mut archive, &opf_path)?;
+        warnings.splice(0..0, container_warning);
+        let text_start = if config.skip_front_matter {
+            text_start_index(&guide, &spine, &manifest).unwrap_or(0)
+        } else {
+            0
+        };
+        let opf_dir = opf_path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir.to_string())
+            .unwrap_or_default();
+        let nav_points =
+            read_nav_points(&mut archive, &opf_dir, nav_href.as_deref(), ncx_href.as_deref(), &manifest);
+
+        Ok(LazyBook {
+            archive,
+            metadata,
+            spine,
+            manifest,
+            media_overlays,
+            nav_points,
+            fixed_layout,
+            text_start,
+            opf_dir,
+            config,
+            cache: HashMap::new(),
+            unreadable: HashSet::new(),
+            warnings,
+        })
+    }
+
+    /// Number of spine items (an upper bound on the number of chapters).
+    pub fn chapter_count(&self) -> usize {
+        self.spine.len()
+    }
+
+    /// Spine index reading starts at: the guide's `text` reference with
+    /// `ParseConfig::skip_front_matter`, otherwise 0. Earlier items return
+    /// `ParseWarning::FrontMatterSkipped` from `get_chapter`.
+    pub fn text_start(&self) -> usize {
+        self.text_start
+    }
+
+    /// Non-fatal issues found so far (OPF plus every chapter loaded).
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Load (or return the cached) chapter at a spine index.
+    ///
+    /// Spine items without readable text (cover pages, missing files) return
+    /// `EpubError::Warning` and should be skipped by the caller.
+    ///
+    /// `Chapter::global_word_start` is filled in when `global_word_start`
+    /// knows it, and is 0 otherwise.
+    pub fn get_chapter(&mut self, index: usize) -> Result<Chapter, EpubError> {
+        self.load(index)?;
+        let mut chapter = self.cache[&index].clone();
+        chapter.global_word_start = self.global_word_start(index).unwrap_or(0);
+        Ok(chapter)
+    }
+
+    /// Global word offset of the chapter at a spine index, as in
+    /// `parse_epub`, or `None` until every earlier spine item has been
+    /// loaded (or found unreadable).
+    pub fn global_word_start(&self, index: usize) -> Option<u32> {
+        (0..index.min(self.spine.len())).try_fold(0u32, |start, i| match self.cache.get(&i) {
+            Some(chapter) => Some(start + chapter.stats.word_count),
+            None => self.unreadable.contains(&i).then_some(start),
+        })
+    }
+
+    /// Load chapters `start..end` into the cache, e.g. to warm up the next few
+    /// chapters from a background thread. Unreadable items are skipped.
+    pub fn preload_range(&mut self, start: usize, end: usize) {
+        for index in start..end.min(self.spine.len()) {
+            let _ = self.load(index);
+        }
+    }
+
+    fn load(&mut self, index: usize) -> Result<(), EpubError> {
+        if self.cache.contains_key(&index) {
+            return Ok(());
+        }
+        let item_id = self.spine.get(index).ok_or_else(|| {
+            EpubError::InvalidStructure(format!("Chapter index out of range: {}", index))
+        })?;
+        if index < self.text_start {
+            self.unreadable.insert(index);
+            return Err(EpubError::Warning(ParseWarning::FrontMatterSkipped(index)));
+        }
+        if self.config.skip_fixed_layout && self.fixed_layout.contains(&index) {
+            self.unreadable.insert(index);
+            return Err(EpubError::Warning(ParseWarning::FixedLayoutSkipped(index)));
+        }
+        let item = SpineItem { index, id: item_id, nav_point: self.nav_points.get(item_id) };
+        let mut chapter = match load_spine_item(
+            &mut self.archive,
+            &self.opf_dir,
+            &self.manifest,
+            item,
+            &self.config,
+            &mut self.warnings,
+        ) {
+            Ok(chapter) => chapter,
+            Err(warning) => {
+                self.unreadable.insert(index);
+                return Err(EpubError::Warning(warning));
+            }
+        };
+        if self.config.extract_audio_metadata {
+            chapter.audio_duration_ms =
+                load_audio_duration(&mut self.archive, &self.opf_dir, &self.media_overlays, item_id);
+        }
+        self.cache.insert(index, chapter);
+        Ok(())
+    }
+}
+
+/// Zip bomb guard, run on the central directory before anything is
+/// decompressed. The sizes checked are the ones the archive declares.
+fn check_archive_size<R: Read + Seek>(archive: &mut ZipArchive<R>, config: &ParseConfig) -> Result<(), EpubError> {
+    let mut compressed: u64 = 0;
+    let mut uncompressed: u64 = 0;
+    let mut content: u64 = 0;
+
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        compressed = compressed.saturating_add(file.compressed_size());
+        uncompressed = uncompressed.saturating_add(file.size());
+        let name = file.name().to_lowercase();
+        if name.ends_with(".xhtml") || name.ends_with(".html") || name.ends_with(".htm") {
+            if file.size() > config.max_xhtml_bytes {
+                return Err(EpubError::TooLarge(file.size()));
+            }
+            content = content.saturating_add(file.size());
+        }
+    }
+
+    if content > config.max_uncompressed_size {
+        return Err(EpubError::TooLarge(content));
+    }
+    let ratio = uncompressed as f64 / compressed.max(1) as f64;
+    if ratio > config.max_compression_ratio {
+        return Err(EpubError::SuspiciousCompression(ratio));
+    }
+    Ok(())
+}
+
+/// Entries looked at by `check_not_encrypted`. A password-protected export
+/// encrypts every entry, so the first few are enough.
+const ENCRYPTION_CHECK_ENTRIES: usize = 8;
+
+/// Fail early with `EpubError::Encrypted` if the first entries have the ZIP
+/// encryption flag set, rather than with a missing container or empty book.
+/// Opening an entry only reads its headers, so nothing is decompressed.
+fn check_not_encrypted<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<(), EpubError> {
+    for i in 0..archive.len().min(ENCRYPTION_CHECK_ENTRIES) {
+        if let Err(e) = archive.by_index(i) {
+            if is_password_error(&e) {
+                return Err(EpubError::Encrypted);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The error the zip crate gives for opening an encrypted entry without a password.
+fn is_password_error(e: &zip::result::ZipError) -> bool {
+    matches!(e, zip::result::ZipError::UnsupportedArchive(detail) if *detail == zip::result::ZipError::PASSWORD_REQUIRED)
 }
 
-fn read_container(archive: &mut ZipArchive<Cursor<&[u8]>>) -> Result<String, EpubError> {
-    let content = read_file(archive, "META-INF/container.xml")?;
-    let content_str = String::from_utf8_lossy(&content);
+/// Path of the OPF package document.
+///
+/// Normally read from `META-INF/container.xml`, using `selector` to pick a
+/// rendition. Stripped EPUBs without one fall back to the first `.opf` entry
+/// in the ZIP (preferring `content.opf`), and report that with a
+/// `ParseWarning::MissingContainer`.
+fn locate_opf<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    selector: &RenditionSelector,
+) -> Result<(String, Option<ParseWarning>), EpubError> {
+    match read_container(archive, selector) {
+        Ok(path) => Ok((path, None)),
+        Err(EpubError::MissingContainer) => {
+            let path = find_opf_entry(archive).ok_or(EpubError::MissingContainer)?;
+            Ok((path.clone(), Some(ParseWarning::MissingContainer(path))))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn find_opf_entry<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<String> {
+    // Archive order, so "first" is deterministic
+    let opf_names: Vec<String> = (0..archive.len())
+        .filter_map(|i| archive.by_index_raw(i).ok().map(|file| file.name().to_string()))
+        .filter(|name| name.to_lowercase().ends_with(".opf"))
+        .collect();
+    let is_content_opf = |name: &str| name.rsplit('/').next().is_some_and(|f| f.eq_ignore_ascii_case("content.opf"));
+    opf_names
+        .iter()
+        .find(|name| is_content_opf(name))
+        .or(opf_names.first())
+        .cloned()
+}
+
+fn read_container<R: Read + Seek>(archive: &mut ZipArchive<R>, selector: &RenditionSelector) -> Result<String, EpubError> {
+    let rootfiles = read_container_all(archive)?;
+    selector
+        .select(&rootfiles)
+        .map(|rootfile| rootfile.full_path.clone())
+        .ok_or_else(missing_opf)
+}
+
+/// A `<rootfile>` entry from `container.xml`
+struct Rootfile {
+    full_path: String,
+    media_type: String,
+}
+
+/// Every rootfile with a `full-path`, in document order. Empty if there are none.
+fn read_container_all<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<Vec<Rootfile>, EpubError> {
+    let content = match read_file(archive, "META-INF/container.xml") {
+        Err(EpubError::InvalidStructure(_)) => return Err(missing_container()),
+        result => result?,
+    };
+    let content_str = decode_xml(&content);
 
     // Parse XML to find rootfile path
     let mut reader = Reader::from_str(&content_str);
     reader.trim_text(true);
 
+    let mut rootfiles = Vec::new();
     let mut buf = Vec::new();
     loop {
         match reader.read_event_into(&mut buf) {
-            Ok(Event::Empty(e)) | Ok(Event::Start(e)) => {
-                if e.name().as_ref() == b"rootfile" {
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"full-path" {
-                            return Ok(String::from_utf8_lossy(&attr.value).to_string());
-                        }
+            Ok(Event::Empty(e)) | Ok(Event::Start(e)) if e.local_name().as_ref() == b"rootfile" => {
+                let mut full_path = None;
+                let mut media_type = String::new();
+                for attr in e.attributes().flatten() {
+                    match attr.key.local_name().as_ref() {
+                        b"full-path" => full_path = Some(String::from_utf8_lossy(&attr.value).to_string()),
+                        b"media-type" => media_type = String::from_utf8_lossy(&attr.value).to_string(),
+                        _ => {}
                     }
                 }
+                if let Some(full_path) = full_path {
+                    rootfiles.push(Rootfile { full_path, media_type });
+                }
             }
             Ok(Event::Eof) => break,
-            Err(e) => return Err(EpubError::Xml(e)),
+            Err(e) => return Err(xml_error(e)),
             _ => {}
         }
         buf.clear();
     }
 
-    Err(EpubError::MissingOpf)
+    Ok(rootfiles)
 }
 
-fn read_opf(
-    archive: &mut ZipArchive<Cursor<&[u8]>>,
-    path: &str,
-) -> Result<(BookMetadata, Vec<String>, HashMap<String, String>), EpubError> {
+// Error constructors for the rare failure paths, kept out of line
+#[cold]
+fn xml_error(e: quick_xml::Error) -> EpubError {
+    EpubError::Xml(e)
+}
+
+#[cold]
+fn missing_container() -> EpubError {
+    EpubError::MissingContainer
+}
+
+#[cold]
+fn missing_opf() -> EpubError {
+    EpubError::MissingOpf
+}
+
+#[cold]
+fn file_not_found(path: &str) -> EpubError {
+    EpubError::InvalidStructure(format!("File not found: {}", path))
+}
+
+/// Parsed OPF package document
+struct OpfPackage {
+    metadata: BookMetadata,
+    /// Manifest ids in reading order
+    spine: Vec<String>,
+    /// Manifest id -> href (XHTML items only)
+    manifest: HashMap<String, String>,
+    /// XHTML manifest id -> href of its Media Overlay SMIL file
+    media_overlays: HashMap<String, String>,
+    /// Href of the EPUB 2 NCX table of contents, if the manifest has one
+    ncx_href: Option<String>,
+    /// Href of the EPUB 3 navigation document, if the manifest has one
+    nav_href: Option<String>,
+    /// Spine indices of fixed-layout (pre-paginated) items
+    fixed_layout: HashSet<usize>,
+    guide: EpubGuide,
+    warnings: Vec<ParseWarning>,
+}
+
+fn read_opf<R: Read + Seek>(archive: &mut ZipArchive<R>, path: &str) -> Result<OpfPackage, EpubError> {
     let content = read_file(archive, path)?;
-    let content_str = String::from_utf8_lossy(&content);
+    let content_str = decode_xml(&content);
 
     let mut reader = Reader::from_str(&content_str);
     reader.trim_text(true);
 
     let mut metadata = BookMetadata::default();
     let mut spine = Vec::new();
-    let mut manifest = HashMap::new();
+    let mut items: Vec<OpfItem> = Vec::new();
+    let mut guide = EpubGuide::default();
+    let mut warnings = Vec::new();
 
     let mut buf = Vec::new();
     let mut in_metadata = false;
     let mut current_tag = String::new();
+    let mut current_has_text = false;
+    // EPUB 3 <meta property="..." id="..." refines="#..."> of the open element
+    let mut current_meta = OpfMeta::default();
+    let mut collections: Vec<(String, String)> = Vec::new(); // (id, name)
+    let mut refinements: HashMap<(String, String), String> = HashMap::new(); // (id, property) -> value
+    let mut pre_paginated = false;
+    let mut spine_layouts: Vec<SpineLayout> = Vec::new();
+    let mut unique_identifier: Option<String> = None;
+    let mut identifiers: Vec<(Option<String>, String)> = Vec::new(); // (id, value)
+    // EPUB 2 style <meta name="..." content="..."/>, e.g. calibre:series
+    let mut named_metas: HashMap<String, String> = HashMap::new();
 
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(e)) => {
-                let name = e.name();
+                let name = e.local_name();
                 let local_name = String::from_utf8_lossy(name.as_ref());
-                let local_name = local_name.split(':').last().unwrap_or(&local_name);
+                let local_name = &*local_name;
 
                 match local_name {
-                    "metadata" => in_metadata = true,
-                    "title" | "creator" if in_metadata => {
-                        current_tag = local_name.to_string();
-                    }
-                    "item" => {
-                        let mut id = String::new();
-                        let mut href = String::new();
-                        let mut media_type = String::new();
-
+                    "package" => {
                         for attr in e.attributes().flatten() {
-                            match attr.key.as_ref() {
-                                b"id" => id = String::from_utf8_lossy(&attr.value).to_string(),
-                                b"href" => href = String::from_utf8_lossy(&attr.value).to_string(),
-                                b"media-type" => {
-                                    media_type = String::from_utf8_lossy(&attr.value).to_string()
+                            match attr.key.local_name().as_ref() {
+                                b"version" => {
+                                    metadata.epub_version = EpubVersion::from_version_attr(&String::from_utf8_lossy(&attr.value));
                                 }
+                                b"unique-identifier" => unique_identifier = Some(String::from_utf8_lossy(&attr.value).to_string()),
                                 _ => {}
                             }
                         }
-
-                        // Only include XHTML content
-                        if media_type.contains("xhtml") || media_type.contains("html") {
-                            manifest.insert(id, href);
-                        }
                     }
-                    "itemref" => {
-                        for attr in e.attributes().flatten() {
-                            if attr.key.as_ref() == b"idref" {
-                                spine.push(String::from_utf8_lossy(&attr.value).to_string());
-                            }
-                        }
+                    "metadata" => in_metadata = true,
+                    "title" | "creator" if in_metadata => {
+                        current_tag = local_name.to_string();
+                        current_has_text = false;
                     }
+                    "meta" | "identifier" if in_metadata => {
+                        current_tag = local_name.to_string();
+                        current_meta = OpfMeta::from_attributes(&e);
+                        current_meta.add_named_to(&mut named_metas);
+                    }
+                    "item" => items.push(OpfItem::from_attributes(&e)),
+                    "itemref" => add_itemref(&mut spine, &mut spine_layouts, &e),
+                    "reference" => add_guide_reference(&mut guide, &e),
                     _ => {}
                 }
             }
             Ok(Event::Empty(e)) => {
-                let name = e.name();
+                let name = e.local_name();
                 let local_name = String::from_utf8_lossy(name.as_ref());
-                let local_name = local_name.split(':').last().unwrap_or(&local_name);
-
-                if local_name == "item" {
-                    let mut id = String::new();
-                    let mut href = String::new();
-                    let mut media_type = String::new();
-
-                    for attr in e.attributes().flatten() {
-                        match attr.key.as_ref() {
-                            b"id" => id = String::from_utf8_lossy(&attr.value).to_string(),
-                            b"href" => href = String::from_utf8_lossy(&attr.value).to_string(),
-                            b"media-type" => {
-                                media_type = String::from_utf8_lossy(&attr.value).to_string()
-                            }
-                            _ => {}
-                        }
-                    }
+                let local_name = &*local_name;
 
-                    if media_type.contains("xhtml") || media_type.contains("html") {
-                        manifest.insert(id, href);
-                    }
+                if in_metadata && matches!(local_name, "title" | "creator") {
+                    warnings.push(ParseWarning::MalformedMetadata(format!("empty dc:{}", local_name)));
+                } else if in_metadata && local_name == "meta" {
+                    OpfMeta::from_attributes(&e).add_named_to(&mut named_metas);
+                } else if local_name == "item" {
+                    items.push(OpfItem::from_attributes(&e));
                 } else if local_name == "itemref" {
-                    for attr in e.attributes().flatten() {
-                        if attr.key.as_ref() == b"idref" {
-                            spine.push(String::from_utf8_lossy(&attr.value).to_string());
-                        }
-                    }
+                    add_itemref(&mut spine, &mut spine_layouts, &e);
+                } else if local_name == "reference" {
+                    add_guide_reference(&mut guide, &e);
                 }
             }
             Ok(Event::Text(e)) => {
                 let text = e.unescape().unwrap_or_default().to_string();
+                if in_metadata && !current_tag.is_empty() {
+                    current_has_text = true;
+                }
                 if in_metadata {
                     match current_tag.as_str() {
                         "title" if metadata.title.is_empty() => metadata.title = text,
                         "creator" if metadata.author.is_none() => metadata.author = Some(text),
+                        "creator" => metadata.additional_authors.push(text),
+                        "identifier" => identifiers.push((current_meta.id.clone(), text.trim().to_string())),
+                        "meta" => match (&current_meta.property, &current_meta.refines) {
+                            (Some(p), None) if p == "rendition:layout" => pre_paginated = text.trim() == "pre-paginated",
+                            (Some(p), None) if p == "dcterms:modified" => metadata.modified_date = Some(text.trim().to_string()),
+                            (Some(p), None) if p == "schema:numberOfPages" => metadata.page_count = text.trim().parse().ok(),
+                            (Some(p), _) if p == "belongs-to-collection" => {
+                                collections.push((current_meta.id.clone().unwrap_or_default(), text));
+                            }
+                            (Some(p), Some(r)) => {
+                                let id = r.trim_start_matches('#').to_string();
+                                refinements.insert((id, p.clone()), text);
+                            }
+                            (Some(p), None) => {
+                                metadata.extra_meta.insert(p.clone(), text);
+                            }
+                            _ => {}
+                        },
                         _ => {}
                     }
                 }
             }
             Ok(Event::End(e)) => {
-                let name = e.name();
+                let name = e.local_name();
                 let local_name = String::from_utf8_lossy(name.as_ref());
-                let local_name = local_name.split(':').last().unwrap_or(&local_name);
+                let local_name = &*local_name;
 
                 if local_name == "metadata" {
                     in_metadata = false;
+                } else if matches!(current_tag.as_str(), "title" | "creator") && !current_has_text {
+                    warnings.push(ParseWarning::MalformedMetadata(format!("empty dc:{}", current_tag)));
                 }
                 current_tag.clear();
             }
@@ -246,27 +1266,649 @@ fn read_opf(
         buf.clear();
     }
 
+    // The identifier the package names as unique, or else the first one
+    metadata.unique_id = identifiers
+        .iter()
+        .find(|(id, _)| id.is_some() && *id == unique_identifier)
+        .or(identifiers.first())
+        .map(|(_, value)| value.clone())
+        .filter(|value| !value.is_empty());
+
     if metadata.title.is_empty() {
         metadata.title = "Unknown Title".to_string();
+    } else {
+        metadata.leading_article = split_leading_article(&metadata.title).0.map(str::to_string);
+    }
+
+    // Prefer a collection explicitly typed as a series
+    let refinement = |id: &str, property: &str| refinements.get(&(id.to_string(), property.to_string()));
+    let series = collections
+        .iter()
+        .find(|(id, _)| refinement(id, "collection-type").is_some_and(|t| t == "series"))
+        .or_else(|| collections.first());
+    if let Some((id, name)) = series {
+        metadata.series = Some(name.clone());
+        metadata.series_index = refinement(id, "group-position").and_then(|p| p.trim().parse().ok());
+    } else if let Some(name) = named_metas.get("calibre:series").filter(|name| !name.trim().is_empty()) {
+        // Calibre's own series metadata, in most books it has exported
+        metadata.series = Some(name.trim().to_string());
+        metadata.series_index = named_metas.get("calibre:series_index").and_then(|i| i.trim().parse().ok());
+    }
+
+    // Only XHTML content is readable; SMIL files are looked up by the
+    // media-overlay attribute of the XHTML item they narrate
+    let smil_hrefs: HashMap<&str, &str> = items
+        .iter()
+        .filter(|item| item.media_type == "application/smil+xml")
+        .map(|item| (item.id.as_str(), item.href.as_str()))
+        .collect();
+    let mut manifest = HashMap::new();
+    let mut media_overlays = HashMap::new();
+    for item in &items {
+        if !(item.media_type.contains("xhtml") || item.media_type.contains("html")) {
+            continue;
+        }
+        if let Some(smil) = item.media_overlay.as_deref().and_then(|id| smil_hrefs.get(id)) {
+            media_overlays.insert(item.id.clone(), percent_decode(smil));
+        }
+        manifest.insert(item.id.clone(), percent_decode(&item.href));
+    }
+    // Spine indices of fixed-layout pages: all of them for a pre-paginated
+    // book unless overridden per item, plus any item marked on its own
+    let fixed_layout: HashSet<usize> = spine_layouts
+        .iter()
+        .enumerate()
+        .filter(|&(_, &layout)| match layout {
+            SpineLayout::Default => pre_paginated,
+            SpineLayout::Reflowable => false,
+            SpineLayout::PrePaginated => true,
+        })
+        .map(|(index, _)| index)
+        .collect();
+    metadata.is_fixed_layout = pre_paginated || !fixed_layout.is_empty();
+
+    let ncx_href = items
+        .iter()
+        .find(|item| item.media_type == "application/x-dtbncx+xml")
+        .map(|item| percent_decode(&item.href));
+    let nav_href = items.iter().find(|item| item.is_nav).map(|item| percent_decode(&item.href));
+
+    Ok(OpfPackage {
+        metadata,
+        spine,
+        manifest,
+        media_overlays,
+        ncx_href,
+        nav_href,
+        fixed_layout,
+        guide,
+        warnings,
+    })
+}
+
+/// Layout of a spine item from its `<itemref properties>`
+#[derive(Clone, Copy)]
+enum SpineLayout {
+    /// Whatever the book's `rendition:layout` says
+    Default,
+    Reflowable,
+    /// `rendition:layout-pre-paginated`, or a `page-spread-*` property,
+    /// which only fixed-layout books use
+    PrePaginated,
+}
+
+/// Record a spine `<itemref>`: its idref and (at the same index) its layout.
+fn add_itemref(spine: &mut Vec<String>, layouts: &mut Vec<SpineLayout>, e: &quick_xml::events::BytesStart) {
+    let mut idref = None;
+    let mut layout = SpineLayout::Default;
+    for attr in e.attributes().flatten() {
+        match attr.key.local_name().as_ref() {
+            b"idref" => idref = Some(String::from_utf8_lossy(&attr.value).to_string()),
+            b"properties" => {
+                for property in String::from_utf8_lossy(&attr.value).split_whitespace() {
+                    let property = property.strip_prefix("rendition:").unwrap_or(property);
+                    match property {
+                        "layout-reflowable" => layout = SpineLayout::Reflowable,
+                        "layout-pre-paginated" | "page-spread-left" | "page-spread-right" | "page-spread-center" => {
+                            layout = SpineLayout::PrePaginated
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(idref) = idref {
+        spine.push(idref);
+        layouts.push(layout);
+    }
+}
+
+/// Record a `<guide><reference type=... href=...>`; the first of each type wins.
+fn add_guide_reference(guide: &mut EpubGuide, e: &quick_xml::events::BytesStart) {
+    let mut kind = String::new();
+    let mut href = None;
+    for attr in e.attributes().flatten() {
+        match attr.key.local_name().as_ref() {
+            b"type" => kind = String::from_utf8_lossy(&attr.value).to_lowercase(),
+            b"href" => href = Some(percent_decode(&String::from_utf8_lossy(&attr.value))),
+            _ => {}
+        }
+    }
+    let slot = match kind.as_str() {
+        "cover" => &mut guide.cover_href,
+        "toc" => &mut guide.toc_href,
+        "text" => &mut guide.text_start_href,
+        _ => return,
+    };
+    if slot.is_none() {
+        *slot = href;
+    }
+}
+
+/// Spine index of the guide's `text` reference, ignoring any `#fragment`.
+fn text_start_index(guide: &EpubGuide, spine: &[String], manifest: &HashMap<String, String>) -> Option<usize> {
+    let href = guide.text_start_href.as_deref()?;
+    let path = href.split('#').next().unwrap_or(href);
+    spine
+        .iter()
+        .position(|id| manifest.get(id).is_some_and(|item| item == path))
+}
+
+/// A spine item's entry in the table of contents (EPUB 3 nav or NCX)
+struct NavPoint {
+    title: String,
+    /// 0 for a top-level entry, 1 for one nested inside it, ...
+    nesting_level: u8,
+}
+
+/// `(href, title, depth)` of a table of contents link
+type TocEntry = (String, String, u8);
+
+/// Chapter titles and nesting levels from the table of contents, keyed by
+/// manifest id.
+///
+/// These are the titles the author chose for the table of contents, so they
+/// take precedence over the `<title>`/heading heuristic. The EPUB 3 nav
+/// document is read first; the NCX is the fallback when there's no nav
+/// document or it has no usable entries. When several entries point into the
+/// same file (e.g. `#section` anchors), the first one wins. A missing or
+/// unparseable table of contents just yields no entries.
+fn read_nav_points<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    opf_dir: &str,
+    nav_href: Option<&str>,
+    ncx_href: Option<&str>,
+    manifest: &HashMap<String, String>,
+) -> HashMap<String, NavPoint> {
+    let ids_by_href: HashMap<String, &String> = manifest
+        .iter()
+        .map(|(id, href)| (resolve_href(opf_dir, href), id))
+        .collect();
+    let sources = [(nav_href, parse_nav as fn(&[u8]) -> Vec<TocEntry>), (ncx_href, parse_ncx)];
+
+    let mut points = HashMap::new();
+    for (href, parse) in sources {
+        let Some(href) = href else {
+            continue;
+        };
+        let full_path = resolve_href(opf_dir, href);
+        let Ok(content) = read_file(archive, &full_path) else {
+            continue;
+        };
+        // Table of contents links are relative to their own file; manifest hrefs to the OPF
+        let toc_dir = full_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        for (src, title, nesting_level) in parse(&content) {
+            let path = src.split('#').next().unwrap_or(&src);
+            if let Some(&id) = ids_by_href.get(&resolve_href(toc_dir, &percent_decode(path))) {
+                points.entry(id.clone()).or_insert(NavPoint { title, nesting_level });
+            }
+        }
+        if !points.is_empty() {
+            break;
+        }
+    }
+    points
+}
+
+/// `(href, title, depth)` of every link in an EPUB 3 nav document's
+/// `<nav epub:type="toc">`, in document order. Depth counts enclosing `<ol>`
+/// lists, so top-level entries are 0. Other navs (landmarks, page-list) and
+/// unlinked `<span>` headings are skipped.
+fn parse_nav(content: &[u8]) -> Vec<TocEntry> {
+    let content_str = decode_xml(content);
+    let mut reader = NsReader::from_str(&content_str);
+    reader.trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut in_toc = false;
+    let mut lists = 0usize;
+    // Href and label text of the open <a>
+    let mut link: Option<(String, String)> = None;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"nav" if !in_toc => in_toc = has_epub_type(&reader, &e, |kind| kind == "toc"),
+                b"ol" if in_toc => lists += 1,
+                b"a" if in_toc => {
+                    link = e
+                        .attributes()
+                        .flatten()
+                        .find(|attr| attr.key.local_name().as_ref() == b"href")
+                        .map(|attr| (String::from_utf8_lossy(&attr.value).to_string(), String::new()));
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                if let Some((_, label)) = link.as_mut() {
+                    let text = e.unescape().unwrap_or_default();
+                    if !label.is_empty() {
+                        label.push(' ');
+                    }
+                    label.push_str(text.trim());
+                }
+            }
+            Ok(Event::End(e)) if in_toc => match e.local_name().as_ref() {
+                b"nav" => break,
+                b"ol" => lists = lists.saturating_sub(1),
+                b"a" => {
+                    if let Some((href, label)) = link.take().filter(|(_, label)| !label.is_empty()) {
+                        let depth = u8::try_from(lists.saturating_sub(1)).unwrap_or(u8::MAX);
+                        entries.push((href, label, depth));
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    entries
+}
+
+/// `(src, title, depth)` of every NCX `navPoint`, in document order. Depth
+/// counts enclosing `navPoint`s, so top-level entries are 0.
+fn parse_ncx(content: &[u8]) -> Vec<TocEntry> {
+    let content_str = decode_xml(content);
+    let mut reader = Reader::from_str(&content_str);
+    reader.trim_text(true);
+
+    let mut entries = Vec::new();
+    // Label of each open navPoint; the label precedes its <content>
+    let mut labels: Vec<String> = Vec::new();
+    let mut in_label = false;
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"navPoint" => labels.push(String::new()),
+                b"navLabel" => in_label = true,
+                _ => {}
+            },
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"content" => {
+                let src = e
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.local_name().as_ref() == b"src")
+                    .map(|attr| String::from_utf8_lossy(&attr.value).to_string());
+                if let (Some(src), Some(label)) = (src, labels.last()) {
+                    if !label.is_empty() {
+                        let depth = u8::try_from(labels.len() - 1).unwrap_or(u8::MAX);
+                        entries.push((src, label.clone(), depth));
+                    }
+                }
+            }
+            Ok(Event::Text(e)) if in_label => {
+                if let Some(label) = labels.last_mut() {
+                    let text = e.unescape().unwrap_or_default();
+                    if !label.is_empty() {
+                        label.push(' ');
+                    }
+                    label.push_str(text.trim());
+                }
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"navPoint" => {
+                    labels.pop();
+                }
+                b"navLabel" => in_label = false,
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    entries
+}
+
+/// Join `href` onto `dir` and resolve any `.` and `..` segments.
+///
+/// An absolute `href` ("/OEBPS/ch1.xhtml") is taken from the archive root
+/// and ignores `dir`.
+fn resolve_href(dir: &str, href: &str) -> String {
+    let dir = if href.starts_with('/') { "" } else { dir };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in dir.split('/').chain(href.split('/')) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// Attributes of a manifest `<item>`
+#[derive(Default)]
+struct OpfItem {
+    id: String,
+    href: String,
+    media_type: String,
+    /// Manifest id of the SMIL file narrating this item (EPUB 3)
+    media_overlay: Option<String>,
+    /// `properties="nav"`: the EPUB 3 navigation document
+    is_nav: bool,
+}
+
+impl OpfItem {
+    fn from_attributes(e: &quick_xml::events::BytesStart) -> Self {
+        let mut item = OpfItem::default();
+        for attr in e.attributes().flatten() {
+            let value = String::from_utf8_lossy(&attr.value).to_string();
+            match attr.key.local_name().as_ref() {
+                b"id" => item.id = value,
+                b"href" => item.href = value,
+                b"media-type" => item.media_type = value,
+                b"media-overlay" => item.media_overlay = Some(value),
+                b"properties" => item.is_nav = value.split_whitespace().any(|property| property == "nav"),
+                _ => {}
+            }
+        }
+        item
+    }
+}
+
+/// Attributes of a `<meta>` element in the OPF metadata: EPUB 3
+/// `property`/`refines`, or EPUB 2 `name`/`content`
+#[derive(Default)]
+struct OpfMeta {
+    property: Option<String>,
+    id: Option<String>,
+    refines: Option<String>,
+    name: Option<String>,
+    content: Option<String>,
+}
+
+impl OpfMeta {
+    fn from_attributes(e: &quick_xml::events::BytesStart) -> Self {
+        let mut meta = OpfMeta::default();
+        for attr in e.attributes().flatten() {
+            let value = Some(String::from_utf8_lossy(&attr.value).to_string());
+            match attr.key.local_name().as_ref() {
+                b"property" => meta.property = value,
+                b"id" => meta.id = value,
+                b"refines" => meta.refines = value,
+                b"name" => meta.name = value,
+                b"content" => meta.content = value,
+                _ => {}
+            }
+        }
+        meta
+    }
+
+    /// Record a `name`/`content` pair; the first of each name wins.
+    fn add_named_to(&self, named_metas: &mut HashMap<String, String>) {
+        if let (Some(name), Some(content)) = (&self.name, &self.content) {
+            named_metas.entry(name.clone()).or_insert_with(|| content.clone());
+        }
+    }
+}
+
+/// How serious a `ValidationIssue` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Violates the EPUB spec in a way that breaks or degrades parsing
+    Error,
+    /// Violates the spec but is handled by the parser
+    Warning,
+}
+
+/// A single spec violation found by `EpubValidator`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Stable machine-readable identifier, e.g. "missing-identifier"
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error(code: &'static str, message: String) -> Self {
+        ValidationIssue { severity: Severity::Error, code, message }
+    }
+
+    fn warning(code: &'static str, message: String) -> Self {
+        ValidationIssue { severity: Severity::Warning, code, message }
+    }
+}
+
+/// Checks an EPUB for spec violations.
+///
+/// Independent of `parse_epub`: a book can parse successfully and still have
+/// issues (e.g. a missing `dc:identifier`), and validation reports as much as
+/// it can even when parsing would fail.
+pub struct EpubValidator;
+
+impl EpubValidator {
+    /// Validate an EPUB, returning every issue found (empty if compliant).
+    pub fn validate(data: &[u8]) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        let mut archive = match ZipArchive::new(Cursor::new(data)) {
+            Ok(archive) => archive,
+            Err(e) => {
+                issues.push(ValidationIssue::error("invalid-zip", e.to_string()));
+                return issues;
+            }
+        };
+
+        if archive.by_name("META-INF/container.xml").is_err() {
+            issues.push(ValidationIssue::error(
+                "missing-container",
+                "META-INF/container.xml not found".to_string(),
+            ));
+            return issues;
+        }
+
+        let opf_path = match read_container(&mut archive, &RenditionSelector::First) {
+            Ok(path) => path,
+            Err(e) => {
+                issues.push(ValidationIssue::error("missing-opf", e.to_string()));
+                return issues;
+            }
+        };
+        let content = match archive.by_name(&opf_path) {
+            Ok(mut file) => {
+                let mut content = Vec::new();
+                match file.read_to_end(&mut content) {
+                    Ok(_) => content,
+                    Err(e) => {
+                        issues.push(ValidationIssue::error("missing-opf", e.to_string()));
+                        return issues;
+                    }
+                }
+            }
+            Err(_) => {
+                issues.push(ValidationIssue::error(
+                    "missing-opf",
+                    format!("OPF file not found: {}", opf_path),
+                ));
+                return issues;
+            }
+        };
+
+        let opf_dir = opf_path
+            .rsplit_once('/')
+            .map(|(dir, _)| dir)
+            .unwrap_or("");
+        let zip_names: HashSet<&str> = archive.file_names().collect();
+        Self::check_opf(&content, opf_dir, &zip_names, &mut issues);
+
+        issues
     }
 
-    Ok((metadata, spine, manifest))
+    fn check_opf(
+        content: &[u8],
+        opf_dir: &str,
+        zip_names: &HashSet<&str>,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        let content_str = decode_xml(content);
+        let mut reader = Reader::from_str(&content_str);
+        reader.trim_text(true);
+
+        let mut manifest: Vec<(String, String)> = Vec::new(); // (id, href)
+        let mut spine: Vec<String> = Vec::new();
+        let mut title: Option<String> = None;
+        let mut has_identifier = false;
+        let mut in_title = false;
+
+        let mut buf = Vec::new();
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                    let name = e.local_name();
+                    let local_name = String::from_utf8_lossy(name.as_ref());
+                    let local_name = &*local_name;
+
+                    match local_name {
+                        "title" => {
+                            in_title = true;
+                            title.get_or_insert_with(String::new);
+                        }
+                        "identifier" => has_identifier = true,
+                        "item" => {
+                            let mut id = String::new();
+                            let mut href = String::new();
+                            for attr in e.attributes().flatten() {
+                                match attr.key.local_name().as_ref() {
+                                    b"id" => id = String::from_utf8_lossy(&attr.value).to_string(),
+                                    b"href" => href = percent_decode(&String::from_utf8_lossy(&attr.value)),
+                                    _ => {}
+                                }
+                            }
+                            manifest.push((id, href));
+                        }
+                        "itemref" => {
+                            for attr in e.attributes().flatten() {
+                                if attr.key.local_name().as_ref() == b"idref" {
+                                    spine.push(String::from_utf8_lossy(&attr.value).to_string());
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::Text(e)) if in_title => {
+                    if let Some(title) = title.as_mut() {
+                        title.push_str(&e.unescape().unwrap_or_default());
+                    }
+                }
+                Ok(Event::End(_)) => in_title = false,
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    issues.push(ValidationIssue::error("invalid-opf", e.to_string()));
+                    break;
+                }
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        match title {
+            None => issues.push(ValidationIssue::error("missing-title", "No dc:title".to_string())),
+            Some(t) if t.trim().is_empty() => {
+                issues.push(ValidationIssue::error("empty-title", "dc:title is empty".to_string()))
+            }
+            _ => {}
+        }
+
+        if !has_identifier {
+            issues.push(ValidationIssue::error(
+                "missing-identifier",
+                "No dc:identifier".to_string(),
+            ));
+        }
+
+        let mut ids = HashSet::new();
+        for (id, href) in &manifest {
+            if !ids.insert(id.as_str()) {
+                issues.push(ValidationIssue::error(
+                    "duplicate-manifest-id",
+                    format!("Duplicate manifest id: {}", id),
+                ));
+            }
+
+            let full_path = resolve_href(opf_dir, href);
+            if !zip_names.contains(full_path.as_str()) {
+                let lower = full_path.to_lowercase();
+                if zip_names.iter().any(|name| name.to_lowercase() == lower) {
+                    issues.push(ValidationIssue::warning(
+                        "manifest-href-case",
+                        format!("Manifest href differs in case from ZIP entry: {}", full_path),
+                    ));
+                } else {
+                    issues.push(ValidationIssue::error(
+                        "manifest-item-missing",
+                        format!("Manifest item {} not in ZIP: {}", id, full_path),
+                    ));
+                }
+            }
+        }
+
+        for idref in &spine {
+            if !ids.contains(idref.as_str()) {
+                issues.push(ValidationIssue::error(
+                    "spine-item-not-in-manifest",
+                    format!("Spine itemref not in manifest: {}", idref),
+                ));
+            }
+        }
+    }
+}
+
+fn read_file<R: Read + Seek>(archive: &mut ZipArchive<R>, path: &str) -> Result<Vec<u8>, EpubError> {
+    read_file_with_limit(archive, path, DEFAULT_MAX_XHTML_BYTES)
 }
 
-fn read_file(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Result<Vec<u8>, EpubError> {
+/// `read_file` failing with `EpubError::TooLarge` instead of reading more
+/// than `max_bytes`.
+fn read_file_with_limit<R: Read + Seek>(archive: &mut ZipArchive<R>, path: &str, max_bytes: u64) -> Result<Vec<u8>, EpubError> {
     // Try exact path first
-    if let Ok(mut file) = archive.by_name(path) {
-        let mut content = Vec::new();
-        file.read_to_end(&mut content)?;
+    if let Some(content) = read_entry(archive, path, max_bytes)? {
         return Ok(content);
     }
 
+    // Hrefs may still be percent-encoded (e.g. "%20" for a space)
+    let decoded = percent_decode(path);
+    if decoded != path {
+        if let Some(content) = read_entry(archive, &decoded, max_bytes)? {
+            return Ok(content);
+        }
+    }
+
     // Try case-insensitive match - first pass to find the name
-    let path_lower = path.to_lowercase();
+    let path_lower = decoded.to_lowercase();
     let mut found_name: Option<String> = None;
 
     for i in 0..archive.len() {
-        if let Ok(file) = archive.by_index(i) {
+        if let Ok(file) = archive.by_index_raw(i) {
             if file.name().to_lowercase() == path_lower {
                 found_name = Some(file.name().to_string());
                 break;
@@ -276,94 +1918,267 @@ fn read_file(archive: &mut ZipArchive<Cursor<&[u8]>>, path: &str) -> Result<Vec<
 
     // Second pass to read the file (separate borrow)
     if let Some(name) = found_name {
-        let mut file = archive.by_name(&name)?;
-        let mut content = Vec::new();
-        file.read_to_end(&mut content)?;
-        return Ok(content);
+        if let Some(content) = read_entry(archive, &name, max_bytes)? {
+            return Ok(content);
+        }
     }
 
-    Err(EpubError::InvalidStructure(format!(
-        "File not found: {}",
-        path
-    )))
+    Err(file_not_found(path))
 }
 
-/// Extract plain text from XHTML, stripping all tags
-fn extract_text_from_xhtml(content: &[u8]) -> String {
-    let content_str = String::from_utf8_lossy(content);
+/// Contents of the entry named exactly `name`, or `None` if it can't be
+/// opened. An encrypted entry is `EpubError::Encrypted`, and one over
+/// `max_bytes` is `EpubError::TooLarge`: checked against the declared size
+/// before reading, and against the bytes read in case the header lies.
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str, max_bytes: u64) -> Result<Option<Vec<u8>>, EpubError> {
+    match archive.by_name(name) {
+        Ok(file) => {
+            if file.size() > max_bytes {
+                return Err(EpubError::TooLarge(file.size()));
+            }
+            let mut content = Vec::with_capacity(file.size() as usize);
+            file.take(max_bytes.saturating_add(1)).read_to_end(&mut content)?;
+            if content.len() as u64 > max_bytes {
+                return Err(EpubError::TooLarge(content.len() as u64));
+            }
+            Ok(Some(content))
+        }
+        Err(e) if is_password_error(&e) => Err(EpubError::Encrypted),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Decode an XML file as UTF-8 (lossily), dropping a leading byte-order
+/// mark; Windows tools often write one before `<?xml`.
+fn decode_xml(content: &[u8]) -> std::borrow::Cow<'_, str> {
+    String::from_utf8_lossy(content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content))
+}
+
+/// Decode `%XX` escapes in a manifest href (e.g. "Chapter%201.xhtml").
+/// Invalid escapes are kept as-is; decoded bytes are interpreted as UTF-8.
+fn percent_decode(s: &str) -> String {
+    if !s.contains('%') {
+        return s.to_string();
+    }
+
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Extract plain text from XHTML, stripping all tags.
+/// Also returns the first XML error hit; parsing continues past errors so
+/// that as much text as possible is kept.
+fn extract_text_from_xhtml(content: &[u8]) -> (String, Option<quick_xml::Error>) {
+    let content_str = decode_xml(content);
     let mut result = String::new();
     let mut in_body = false;
     let mut skip_depth = 0;
+    // Inside <ruby>, base text segments are joined without a space
+    let mut ruby_depth = 0;
+    let mut ruby_joined = false;
+    // Element depth inside a footnote/endnote, which is kept out of the word stream
+    let mut note_depth = 0;
+    let mut first_error = None;
+    let mut seen_root = false;
 
-    let mut reader = Reader::from_str(&content_str);
+    let mut reader = NsReader::from_str(&content_str);
     reader.trim_text(true);
 
     let mut buf = Vec::new();
 
     loop {
         match reader.read_event_into(&mut buf) {
+            // An NCX mislabelled as XHTML in the manifest has no readable text,
+            // and must not reach the lenient HTML fallback either
+            Ok(Event::Start(e)) if !seen_root && e.local_name().as_ref() == b"ncx" => {
+                return (String::new(), None);
+            }
+            Ok(Event::Start(_)) if note_depth > 0 => note_depth += 1,
+            Ok(Event::End(_)) if note_depth > 0 => note_depth -= 1,
+            Ok(Event::Start(e)) if in_body && is_note_element(&reader, &e) => note_depth = 1,
             Ok(Event::Start(e)) => {
-                let name = e.name();
+                seen_root = true;
+                let name = e.local_name();
                 let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
 
                 if tag == "body" {
                     in_body = true;
                 } else if in_body {
-                    // Skip script, style, etc.
-                    if matches!(tag.as_str(), "script" | "style" | "head") {
+                    // Skip script, style, ruby annotations, etc.
+                    if matches!(tag.as_str(), "script" | "style" | "head" | "rt") {
                         skip_depth += 1;
                     }
+                    if tag == "ruby" {
+                        ruby_depth += 1;
+                        ruby_joined = false;
+                    }
                     // Add paragraph breaks
-                    if matches!(tag.as_str(), "p" | "div" | "br" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6") {
-                        if !result.ends_with("\n\n") && !result.is_empty() {
-                            result.push_str("\n\n");
-                        }
+                    if matches!(tag.as_str(), "p" | "div" | "br" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+                        && !result.ends_with("\n\n")
+                        && !result.is_empty()
+                    {
+                        result.push_str("\n\n");
                     }
                 }
             }
             Ok(Event::End(e)) => {
-                let name = e.name();
+                let name = e.local_name();
                 let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
 
                 if tag == "body" {
                     in_body = false;
-                } else if matches!(tag.as_str(), "script" | "style" | "head") && skip_depth > 0 {
+                } else if matches!(tag.as_str(), "script" | "style" | "head" | "rt") && skip_depth > 0 {
                     skip_depth -= 1;
+                } else if tag == "ruby" && ruby_depth > 0 {
+                    ruby_depth -= 1;
+                    ruby_joined = false;
                 }
             }
+            Ok(Event::Text(e)) if in_body && skip_depth == 0 && note_depth == 0 => {
+                push_text_segment(&mut result, &unescape_xhtml_text(&e), ruby_depth > 0, &mut ruby_joined);
+            }
+            // Scripts and styles wrapped in CDATA are skipped with their
+            // element; CDATA anywhere else in the body is read as text
+            Ok(Event::CData(e)) if in_body && skip_depth == 0 && note_depth == 0 => {
+                push_text_segment(&mut result, &String::from_utf8_lossy(&e), ruby_depth > 0, &mut ruby_joined);
+            }
+            Ok(Event::Empty(e)) if in_body && note_depth == 0 => {
+                let name = e.local_name();
+                let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
+                if tag == "br" {
+                    result.push_str("\n\n");
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) if first_error.is_none() => first_error = Some(e),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    (result, first_error)
+}
+
+/// Append a run of text to `result`, separated from the previous run by a
+/// space unless both are base text of the same `<ruby>`.
+fn push_text_segment(result: &mut String, text: &str, in_ruby: bool, ruby_joined: &mut bool) {
+    let text = text.trim();
+    if text.is_empty() {
+        return;
+    }
+    if !result.is_empty() && !result.ends_with('\n') && !result.ends_with(' ') && !*ruby_joined {
+        result.push(' ');
+    }
+    result.push_str(text);
+    *ruby_joined = in_ruby;
+}
+
+/// Text content with entities decoded. Numeric character references that
+/// survive unescaping (double-escaped as `&amp;#8220;` by some converters)
+/// are decoded too, and HTML named entities such as `&nbsp;`, which make
+/// quick-xml's unescape fail, are decoded instead of dropping the text.
+fn unescape_xhtml_text(e: &quick_xml::events::BytesText) -> String {
+    match e.unescape() {
+        Ok(text) => crate::tokenizer::decode_char_refs(&text),
+        Err(_) => crate::tokenizer::decode_html_entities(&String::from_utf8_lossy(e)),
+    }
+}
+
+/// Namespace of the `epub:type` attribute
+const OPS_NAMESPACE: &[u8] = b"http://www.idpf.org/2007/ops";
+
+/// True for elements marked `epub:type="footnote"` or `"endnote"`, under
+/// whatever prefix the document binds the OPS namespace to (`ops:type`, ...).
+/// An undeclared `epub:` prefix is accepted too, as many books omit it.
+fn is_note_element<R>(reader: &NsReader<R>, e: &quick_xml::events::BytesStart) -> bool {
+    has_epub_type(reader, e, is_note_type)
+}
+
+/// True if the element's OPS `type` attribute (see `is_note_element`) has a
+/// token matching `kind`.
+fn has_epub_type<R>(reader: &NsReader<R>, e: &quick_xml::events::BytesStart, kind: impl Fn(&str) -> bool) -> bool {
+    use quick_xml::name::{Namespace, ResolveResult};
+
+    e.attributes().flatten().any(|attr| {
+        attr.key.local_name().as_ref() == b"type"
+            && match reader.resolve_attribute(attr.key).0 {
+                ResolveResult::Bound(Namespace(ns)) => ns == OPS_NAMESPACE,
+                ResolveResult::Unknown(prefix) => prefix == b"epub",
+                ResolveResult::Unbound => false,
+            }
+            && String::from_utf8_lossy(&attr.value).split_whitespace().any(&kind)
+    })
+}
+
+/// Collect footnotes and endnotes (`<aside epub:type="footnote">` etc.)
+/// from XHTML. These are excluded from the chapter's word stream.
+fn extract_footnotes(content: &[u8]) -> Vec<Footnote> {
+    let content_str = decode_xml(content);
+    let mut reader = NsReader::from_str(&content_str);
+    reader.trim_text(true);
+
+    let mut footnotes = Vec::new();
+    let mut current: Option<Footnote> = None;
+    let mut depth = 0;
+
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(_)) if current.is_some() => depth += 1,
+            Ok(Event::Start(e)) if is_note_element(&reader, &e) => {
+                let id = e
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.as_ref() == b"id")
+                    .map(|attr| String::from_utf8_lossy(&attr.value).to_string())
+                    .unwrap_or_default();
+                current = Some(Footnote { id, text: String::new() });
+                depth = 1;
+            }
             Ok(Event::Text(e)) => {
-                if in_body && skip_depth == 0 {
+                if let Some(note) = current.as_mut() {
                     let text = e.unescape().unwrap_or_default();
                     let text = text.trim();
                     if !text.is_empty() {
-                        if !result.is_empty() && !result.ends_with('\n') && !result.ends_with(' ') {
-                            result.push(' ');
+                        if !note.text.is_empty() {
+                            note.text.push(' ');
                         }
-                        result.push_str(text);
+                        note.text.push_str(text);
                     }
                 }
             }
-            Ok(Event::Empty(e)) => {
-                if in_body {
-                    let name = e.name();
-                    let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
-                    if tag == "br" {
-                        result.push_str("\n\n");
-                    }
+            Ok(Event::End(_)) if current.is_some() => {
+                depth -= 1;
+                if depth == 0 {
+                    footnotes.extend(current.take());
                 }
             }
-            Ok(Event::Eof) => break,
+            Ok(Event::Eof) | Err(_) => break,
             _ => {}
         }
         buf.clear();
     }
 
-    result
+    footnotes
 }
 
 /// Try to extract a title from XHTML (first h1/h2 or title tag)
 fn extract_title_from_xhtml(content: &[u8]) -> Option<String> {
-    let content_str = String::from_utf8_lossy(content);
+    let content_str = decode_xml(content);
     let mut reader = Reader::from_str(&content_str);
     reader.trim_text(true);
 
@@ -374,7 +2189,7 @@ fn extract_title_from_xhtml(content: &[u8]) -> Option<String> {
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(e)) => {
-                let name = e.name();
+                let name = e.local_name();
                 let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
 
                 if tag == "title" {
@@ -398,8 +2213,14 @@ fn extract_title_from_xhtml(content: &[u8]) -> Option<String> {
                     }
                 }
             }
+            Ok(Event::CData(e)) if in_h_tag => {
+                let text = String::from_utf8_lossy(&e).trim().to_string();
+                if !text.is_empty() {
+                    return Some(text);
+                }
+            }
             Ok(Event::End(e)) => {
-                let name = e.name();
+                let name = e.local_name();
                 let tag = String::from_utf8_lossy(name.as_ref()).to_lowercase();
 
                 if tag == "title" {
@@ -420,12 +2241,1449 @@ fn extract_title_from_xhtml(content: &[u8]) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
+
+    const CONTAINER_XML: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+    /// Build an in-memory EPUB from (path, content) entries.
+    fn build_epub(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (path, content) in files {
+            writer.start_file(*path, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_missing_spine_files() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Holes</dc:title></metadata>
+  <manifest>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="text/c2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c3" href="c3.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="c1"/><itemref idref="c2"/><itemref idref="c3"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>First chapter.</p></body></html>"),
+            ("OEBPS/c3.xhtml", "<html><body><p>Third chapter.</p></body></html>"),
+        ]);
+        let book = parse_epub(&data).unwrap();
+        let indices: Vec<u32> = book.chapters.iter().map(|c| c.index).collect();
+        assert_eq!(indices, vec![0, 2]);
+        assert_eq!(
+            book.warnings,
+            vec![ParseWarning::MissingSpineItem {
+                item_id: "c2".to_string(),
+                href: "OEBPS/text/c2.xhtml".to_string(),
+            }]
+        );
+
+        // With every spine file missing there is no book to return
+        let data = build_epub(&[("META-INF/container.xml", CONTAINER_XML), ("OEBPS/content.opf", opf)]);
+        assert!(matches!(parse_epub(&data), Err(EpubError::InvalidStructure(_))));
+    }
+
+    #[test]
+    fn test_parse_collects_warnings() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Warnings</dc:title>
+    <dc:creator></dc:creator>
+  </metadata>
+  <manifest>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="c2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="gone" href="gone.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="c1"/>
+    <itemref idref="c2"/>
+    <itemref idref="gone"/>
+    <itemref idref="unknown"/>
+  </spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Some text.</p></body></html>"),
+            ("OEBPS/c2.xhtml", "<html><body></body></html>"),
+        ]);
+
+        let book = parse_epub_with_config(&data, &ParseConfig::default()).unwrap();
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(
+            book.warnings,
+            vec![
+                ParseWarning::MalformedMetadata("empty dc:creator".to_string()),
+                ParseWarning::EmptyChapter(1),
+                ParseWarning::MissingSpineItem {
+                    item_id: "gone".to_string(),
+                    href: "OEBPS/gone.xhtml".to_string(),
+                },
+                ParseWarning::MissingSpineItem {
+                    item_id: "unknown".to_string(),
+                    href: String::new(),
+                },
+            ]
+        );
+
+        let strict = ParseConfig {
+            treat_warnings_as_errors: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_epub_with_config(&data, &strict),
+            Err(EpubError::Warning(ParseWarning::MalformedMetadata(_)))
+        ));
+    }
 
     #[test]
     fn test_extract_text_simple() {
         let html = b"<html><body><p>Hello world.</p><p>Second paragraph.</p></body></html>";
-        let text = extract_text_from_xhtml(html);
+        let (text, _) = extract_text_from_xhtml(html);
         assert!(text.contains("Hello world."));
         assert!(text.contains("Second paragraph."));
     }
+
+    #[test]
+    fn test_extract_text_reports_xml_errors() {
+        let html = b"<html><body><p>Hello <b>world.</p><p>After.</p></body></html>";
+        let (text, error) = extract_text_from_xhtml(html);
+        assert!(error.is_some());
+        assert!(text.contains("Hello"));
+    }
+
+    #[test]
+    fn test_extract_text_cdata() {
+        let html = b"<html><head><style><![CDATA[p { margin: 0 }]]></style></head><body>\
+            <h1><![CDATA[Chapter <One>]]></h1>\
+            <script><![CDATA[if (a < b) { run(); }]]></script>\
+            <p>Plain and <![CDATA[wrapped & raw]]> text.</p></body></html>";
+        let (text, error) = extract_text_from_xhtml(html);
+        assert!(error.is_none());
+        assert_eq!(text, "Chapter <One>\n\nPlain and wrapped & raw text.");
+        assert_eq!(extract_title_from_xhtml(html).as_deref(), Some("Chapter <One>"));
+    }
+
+    #[test]
+    fn test_extract_text_character_references() {
+        let html = b"<html><body><p>&#8220;Wait&#160;here&#x2014;now,&#8221; she said.</p></body></html>";
+        let (text, error) = extract_text_from_xhtml(html);
+        assert!(error.is_none());
+        assert_eq!(text, "\u{201C}Wait\u{A0}here\u{2014}now,\u{201D} she said.");
+
+        // Double-escaped references and HTML-only entities
+        let html = b"<html><body><p>&amp;#8220;Quoted&amp;#x201D; &amp;amp; kept</p><p>Non&nbsp;breaking &amp; more</p></body></html>";
+        let (text, _) = extract_text_from_xhtml(html);
+        assert_eq!(text, "\u{201C}Quoted\u{201D} &amp; kept\n\nNon breaking & more");
+    }
+
+    #[test]
+    fn test_validator_accepts_compliant_epub() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="uid">urn:uuid:1234</dc:identifier>
+    <dc:title>Valid</dc:title>
+  </metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+        ]);
+        assert_eq!(EpubValidator::validate(&data), Vec::new());
+    }
+
+    #[test]
+    fn test_validator_reports_violations() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>  </dc:title>
+  </metadata>
+  <manifest>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="C2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="img" href="cover.jpg" media-type="image/jpeg"/>
+  </manifest>
+  <spine><itemref idref="c1"/><itemref idref="nav"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+            ("OEBPS/c2.xhtml", "<html><body><p>Text.</p></body></html>"),
+        ]);
+
+        let issues = EpubValidator::validate(&data);
+        let codes: Vec<&str> = issues.iter().map(|i| i.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "empty-title",
+                "missing-identifier",
+                "duplicate-manifest-id",
+                "manifest-href-case",
+                "manifest-item-missing",
+                "spine-item-not-in-manifest",
+            ]
+        );
+        assert_eq!(issues[3].severity, Severity::Warning);
+        // The same book still parses
+        assert!(parse_epub(&data).is_ok());
+    }
+
+    #[test]
+    fn test_validator_missing_container() {
+        let data = build_epub(&[("OEBPS/content.opf", "<package/>")]);
+        let issues = EpubValidator::validate(&data);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, "missing-container");
+    }
+
+    #[test]
+    fn test_parse_without_container_uses_root_opf() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>No Container</dc:title></metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("other.opf", "<package/>"),
+            ("content.opf", opf),
+            ("c1.xhtml", "<html><body><p>Recovered text.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.metadata.title, "No Container");
+        assert_eq!(book.stats.total_words, 2);
+        assert_eq!(book.warnings[0], ParseWarning::MissingContainer("content.opf".to_string()));
+        assert_eq!(read_epub_metadata(&data).unwrap().title, "No Container");
+
+        let strict = ParseConfig {
+            treat_warnings_as_errors: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_epub_with_config(&data, &strict),
+            Err(EpubError::Warning(ParseWarning::MissingContainer(_)))
+        ));
+
+        let no_opf = build_epub(&[("c1.xhtml", "<html><body><p>Text.</p></body></html>")]);
+        assert!(matches!(parse_epub(&no_opf), Err(EpubError::MissingContainer)));
+    }
+
+    #[test]
+    fn test_parse_epub_with_progress() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub")).unwrap();
+        let mut calls = Vec::new();
+        let book = parse_epub_with_progress(&data, &ParseConfig::default(), |done, total| calls.push((done, total))).unwrap();
+
+        let total = calls[0].1;
+        assert!(total >= book.chapters.len());
+        assert_eq!(calls, (1..=total).map(|done| (done, total)).collect::<Vec<_>>());
+        assert_eq!(book.stats.total_words, parse_epub(&data).unwrap().stats.total_words);
+    }
+
+    #[test]
+    fn test_guide_references_and_front_matter() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Guided</dc:title></metadata>
+  <manifest>
+    <item id="cover" href="cover.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="text/c1.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="cover"/><itemref idref="c1"/></spine>
+  <guide>
+    <reference type="cover" title="Cover" href="cover.xhtml"/>
+    <reference type="text" title="Start" href="text/c1.xhtml#start"/>
+  </guide>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/cover.xhtml", "<html><body><p>Cover page.</p></body></html>"),
+            ("OEBPS/text/c1.xhtml", "<html><body><p>Real content.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.guide.cover_href.as_deref(), Some("cover.xhtml"));
+        assert_eq!(book.guide.text_start_href.as_deref(), Some("text/c1.xhtml#start"));
+        assert_eq!(book.guide.toc_href, None);
+        assert_eq!(book.chapters.len(), 2);
+
+        let config = ParseConfig {
+            skip_front_matter: true,
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].index, 1);
+        assert_eq!(book.chapters[0].words[0].text, "Real");
+
+        let mut lazy = LazyBook::open(data.clone(), config).unwrap();
+        assert_eq!(lazy.text_start(), 1);
+        assert!(matches!(lazy.get_chapter(0), Err(EpubError::Warning(ParseWarning::FrontMatterSkipped(0)))));
+        let chapter = lazy.get_chapter(1).unwrap();
+        assert_eq!(chapter.words[0].text, "Real");
+        assert_eq!(chapter.global_word_start, 0);
+        assert!(lazy.warnings().is_empty());
+
+        let mut lazy = LazyBook::open(data, ParseConfig::default()).unwrap();
+        assert_eq!(lazy.text_start(), 0);
+        assert!(lazy.get_chapter(0).is_ok());
+    }
+
+    #[test]
+    fn test_ncx_titles_override_heuristic() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Titled</dc:title></metadata>
+  <manifest>
+    <item id="ncx" href="toc/toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="c1" href="text/c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="text/c2.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine toc="ncx"><itemref idref="c1"/><itemref idref="c2"/></spine>
+</package>"#;
+        let ncx = r#"<?xml version="1.0"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1"><navMap>
+  <navPoint id="n1" playOrder="1">
+    <navLabel><text>The Beginning</text></navLabel>
+    <content src="../text/c1.xhtml"/>
+    <navPoint id="n1a" playOrder="2">
+      <navLabel><text>A Subsection</text></navLabel>
+      <content src="../text/c1.xhtml#sub"/>
+    </navPoint>
+  </navPoint>
+</navMap></ncx>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/toc/toc.ncx", ncx),
+            ("OEBPS/text/c1.xhtml", "<html><body><h1>1</h1><p>First.</p></body></html>"),
+            ("OEBPS/text/c2.xhtml", "<html><body><h1>Heading Two</h1><p>Second.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.chapters[0].title, "The Beginning");
+        assert_eq!(book.chapters[1].title, "Heading Two");
+        assert_eq!(book.chapters[0].nesting_level, 0);
+
+        let mut lazy = LazyBook::open(data, ParseConfig::default()).unwrap();
+        assert_eq!(lazy.get_chapter(0).unwrap().title, "The Beginning");
+    }
+
+    #[test]
+    fn test_nav_document_titles() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Titled</dc:title></metadata>
+  <manifest>
+    <item id="nav" href="toc/nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="c1" href="text/c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="text/c2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c3" href="text/c3.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine toc="ncx"><itemref idref="c1"/><itemref idref="c2"/><itemref idref="c3"/></spine>
+</package>"#;
+        let nav = r#"<?xml version="1.0"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops"><body>
+  <nav epub:type="landmarks"><ol><li><a href="../text/c3.xhtml">Landmark</a></li></ol></nav>
+  <nav epub:type="toc"><ol>
+    <li><a href="../text/c1.xhtml">Part <em>One</em></a>
+      <ol><li><a href="../text/c2.xhtml#start">Nested</a></li></ol>
+    </li>
+    <li><span>Unlinked</span></li>
+  </ol></nav>
+</body></html>"#;
+        let ncx = r#"<?xml version="1.0"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1"><navMap>
+  <navPoint id="n1"><navLabel><text>From NCX</text></navLabel><content src="text/c1.xhtml"/></navPoint>
+</navMap></ncx>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/toc/nav.xhtml", nav),
+            ("OEBPS/toc.ncx", ncx),
+            ("OEBPS/text/c1.xhtml", "<html><body><h1>1</h1><p>First.</p></body></html>"),
+            ("OEBPS/text/c2.xhtml", "<html><body><p>Second.</p></body></html>"),
+            ("OEBPS/text/c3.xhtml", "<html><body><h1>Heading Three</h1><p>Third.</p></body></html>"),
+        ]);
+
+        // The nav document wins over the NCX; its landmarks are not titles
+        let book = parse_epub(&data).unwrap();
+        let titles: Vec<&str> = book.chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["Part One", "Nested", "Heading Three"]);
+        assert_eq!(book.chapters.iter().map(|c| c.nesting_level).collect::<Vec<_>>(), vec![0, 1, 0]);
+
+        let mut lazy = LazyBook::open(data, ParseConfig::default()).unwrap();
+        assert_eq!(lazy.get_chapter(1).unwrap().title, "Nested");
+    }
+
+    #[test]
+    fn test_default_config_matches_parse_epub() {
+        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(epub_path).expect("Failed to read demo.epub");
+        let book = parse_epub(&data).unwrap();
+        let with_config = parse_epub_with_config(&data, &ParseConfig::default()).unwrap();
+        assert_eq!(format!("{:?}", with_config), format!("{:?}", book));
+    }
+
+    #[test]
+    fn test_parse_is_deterministic() {
+        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(epub_path).expect("Failed to read demo.epub");
+        let first = parse_epub(&data).unwrap();
+        let second = parse_epub(&data).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.chapters, second.chapters);
+
+        let mut shorter = second.clone();
+        shorter.chapters[0].words.pop();
+        assert_ne!(first, shorter);
+    }
+
+    #[test]
+    fn test_absolute_and_parent_relative_hrefs() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Paths</dc:title></metadata>
+  <manifest>
+    <item id="ncx" href="/OEBPS/toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="c1" href="/OEBPS/chapters/ch1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="../chapters/ch2.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine toc="ncx"><itemref idref="c1"/><itemref idref="c2"/></spine>
+</package>"#;
+        let ncx = r#"<?xml version="1.0"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1"><navMap>
+  <navPoint id="n1"><navLabel><text>Absolute</text></navLabel><content src="chapters/ch1.xhtml"/></navPoint>
+  <navPoint id="n2"><navLabel><text>Relative</text></navLabel><content src="chapters/ch2.xhtml"/></navPoint>
+</navMap></ncx>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", &CONTAINER_XML.replace("OEBPS/content.opf", "OEBPS/package/content.opf")),
+            ("OEBPS/package/content.opf", opf),
+            ("OEBPS/toc.ncx", ncx),
+            ("OEBPS/chapters/ch1.xhtml", "<html><body><p>First chapter.</p></body></html>"),
+            ("OEBPS/chapters/ch2.xhtml", "<html><body><p>Second chapter.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert!(book.warnings.is_empty(), "{:?}", book.warnings);
+        assert_eq!(book.chapters.iter().map(|c| c.title.as_str()).collect::<Vec<_>>(), vec!["Absolute", "Relative"]);
+        assert_eq!(book.stats.total_words, 4);
+        assert!(EpubValidator::validate(&data).iter().all(|issue| issue.code != "manifest-item-missing"));
+
+        assert_eq!(resolve_href("OEBPS", "/OEBPS/ch1.xhtml"), "OEBPS/ch1.xhtml");
+        assert_eq!(resolve_href("OEBPS/package", "../ch1.xhtml"), "OEBPS/ch1.xhtml");
+    }
+
+    #[test]
+    fn test_ncx_nesting_level() {
+        let ncx = br#"<?xml version="1.0"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1"><navMap>
+  <navPoint id="p1"><navLabel><text>Part One</text></navLabel><content src="c1.xhtml"/>
+    <navPoint id="c2"><navLabel><text>Chapter 1</text></navLabel><content src="c2.xhtml"/>
+      <navPoint id="c2a"><navLabel><text>Section 1.1</text></navLabel><content src="c3.xhtml"/></navPoint>
+    </navPoint>
+  </navPoint>
+  <navPoint id="p2"><navLabel><text>Part Two</text></navLabel><content src="c4.xhtml"/></navPoint>
+</navMap></ncx>"#;
+        let depths: Vec<(String, u8)> = parse_ncx(ncx).into_iter().map(|(src, _, depth)| (src, depth)).collect();
+        assert_eq!(
+            depths,
+            vec![
+                ("c1.xhtml".to_string(), 0),
+                ("c2.xhtml".to_string(), 1),
+                ("c3.xhtml".to_string(), 2),
+                ("c4.xhtml".to_string(), 0),
+            ]
+        );
+
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Nested</dc:title></metadata>
+  <manifest>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="c2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c3" href="c3.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c4" href="c4.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine toc="ncx"><itemref idref="c1"/><itemref idref="c2"/><itemref idref="c3"/><itemref idref="c4"/></spine>
+</package>"#;
+        let page = "<html><body><p>Some text.</p></body></html>";
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/toc.ncx", std::str::from_utf8(ncx).unwrap()),
+            ("OEBPS/c1.xhtml", page),
+            ("OEBPS/c2.xhtml", page),
+            ("OEBPS/c3.xhtml", page),
+            ("OEBPS/c4.xhtml", page),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.chapters.iter().map(|c| c.nesting_level).collect::<Vec<_>>(), vec![0, 1, 2, 0]);
+
+        let config = ParseConfig {
+            max_chapter_depth: 1,
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.iter().map(|c| c.title.as_str()).collect::<Vec<_>>(), vec!["Part One", "Chapter 1", "Part Two"]);
+        assert!(book.warnings.is_empty());
+
+        let config = ParseConfig {
+            max_chapter_depth: 0,
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.iter().map(|c| c.index).collect::<Vec<_>>(), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_fixed_layout_detection() {
+        let reflowable_with_spreads = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Comic</dc:title></metadata>
+  <manifest>
+    <item id="intro" href="intro.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p1" href="p1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p2" href="p2.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="intro"/>
+    <itemref idref="p1" properties="rendition:page-spread-left"/>
+    <itemref idref="p2" properties="page-spread-right"/>
+  </spine>
+</package>"#;
+        let files = |opf| {
+            build_epub(&[
+                ("META-INF/container.xml", CONTAINER_XML),
+                ("OEBPS/content.opf", opf),
+                ("OEBPS/intro.xhtml", "<html><body><p>Read me.</p></body></html>"),
+                ("OEBPS/p1.xhtml", "<html><body><img src='p1.jpg'/><p>Bam!</p></body></html>"),
+                ("OEBPS/p2.xhtml", "<html><body><img src='p2.jpg'/><p>Pow!</p></body></html>"),
+            ])
+        };
+        let data = files(reflowable_with_spreads);
+
+        let book = parse_epub(&data).unwrap();
+        assert!(book.metadata.is_fixed_layout);
+        assert_eq!(book.chapters.len(), 3);
+
+        let config = ParseConfig {
+            skip_fixed_layout: true,
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(
+            book.warnings,
+            vec![ParseWarning::FixedLayoutSkipped(1), ParseWarning::FixedLayoutSkipped(2)]
+        );
+        let mut lazy = LazyBook::open(data, config.clone()).unwrap();
+        assert!(lazy.get_chapter(0).is_ok());
+        assert!(matches!(lazy.get_chapter(1), Err(EpubError::Warning(ParseWarning::FixedLayoutSkipped(1)))));
+
+        // Book-wide pre-paginated, with one reflowable exception
+        let pre_paginated = reflowable_with_spreads
+            .replace("<dc:title>Comic</dc:title>", "<dc:title>Comic</dc:title><meta property=\"rendition:layout\">pre-paginated</meta>")
+            .replace("<itemref idref=\"intro\"/>", "<itemref idref=\"intro\" properties=\"rendition:layout-reflowable\"/>")
+            .replace(" properties=\"page-spread-right\"", "");
+        let book = parse_epub_with_config(&files(&pre_paginated), &config).unwrap();
+        assert!(book.metadata.is_fixed_layout);
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].words[0].text, "Read");
+
+        let book = parse_epub(&build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", &reflowable_with_spreads.replace(" properties=\"rendition:page-spread-left\"", "").replace(" properties=\"page-spread-right\"", "")),
+            ("OEBPS/intro.xhtml", "<html><body><p>Read me.</p></body></html>"),
+        ]))
+        .unwrap();
+        assert!(!book.metadata.is_fixed_layout);
+    }
+
+    #[test]
+    fn test_rendition_selector() {
+        let container = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/reflow.opf" media-type="application/oebps-package+xml"/>
+    <rootfile full-path="OEBPS/fixed.opf" media-type="application/x-fixed-package+xml"/>
+  </rootfiles>
+</container>"#;
+        let opf = |title: &str| {
+            format!(
+                r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>{}</dc:title></metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#,
+                title
+            )
+        };
+        let (reflow, fixed) = (opf("Reflowable"), opf("Fixed"));
+        let data = build_epub(&[
+            ("META-INF/container.xml", container),
+            ("OEBPS/reflow.opf", &reflow),
+            ("OEBPS/fixed.opf", &fixed),
+            ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+        ]);
+
+        let mut archive = ZipArchive::new(Cursor::new(&data[..])).unwrap();
+        let paths: Vec<String> = read_container_all(&mut archive).unwrap().into_iter().map(|r| r.full_path).collect();
+        assert_eq!(paths, vec!["OEBPS/reflow.opf", "OEBPS/fixed.opf"]);
+
+        let title = |selector: RenditionSelector| {
+            let config = ParseConfig {
+                rendition_selector: selector,
+                ..Default::default()
+            };
+            parse_epub_with_config(&data, &config).unwrap().metadata.title
+        };
+        assert_eq!(title(RenditionSelector::First), "Reflowable");
+        assert_eq!(title(RenditionSelector::Last), "Fixed");
+        assert_eq!(title(RenditionSelector::ByMediaType("application/x-fixed-package+xml".into())), "Fixed");
+        assert_eq!(title(RenditionSelector::ByMediaType("text/plain".into())), "Reflowable");
+    }
+
+    #[test]
+    fn test_content_filter() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Filtered</dc:title></metadata>
+  <manifest>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="about" href="about.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c2" href="c2.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="c1"/><itemref idref="about"/><itemref idref="c2"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><h1>One</h1><p>First chapter.</p></body></html>"),
+            ("OEBPS/about.xhtml", "<html><body><h1>About the Author</h1><p>Lives somewhere.</p></body></html>"),
+            ("OEBPS/c2.xhtml", "<html><body><h1>Two</h1><p>Second chapter.</p></body></html>"),
+        ]);
+
+        let config = ParseConfig {
+            content_filter: Some(Arc::new(|chapter: &Chapter| !chapter.title.starts_with("About the"))),
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.iter().map(|c| c.index).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(book.stats.total_words, 6);
+        assert_eq!(book.chapters[1].global_word_start, 3);
+        assert!(book.warnings.is_empty());
+        assert!(format!("{:?}", config).contains("content_filter: Some(\"<fn>\")"));
+    }
+
+    #[test]
+    fn test_merge_sequential_short_chapters() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Split</dc:title></metadata>
+  <manifest>
+    <item id="p1" href="part1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p1a" href="part1a.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p1b" href="part1b.xhtml" media-type="application/xhtml+xml"/>
+    <item id="p2" href="part2.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="p1"/><itemref idref="p1a"/><itemref idref="p1b"/><itemref idref="p2"/></spine>
+</package>"#;
+        let long = format!("<html><body><p>{}</p></body></html>", "word ".repeat(SHORT_CHAPTER_WORDS as usize));
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/part1.xhtml", "<html><body><h1>Part One</h1><p>It begins.</p></body></html>"),
+            ("OEBPS/part1a.xhtml", "<html><body><p>It continues.</p></body></html>"),
+            ("OEBPS/part1b.xhtml", &long),
+            ("OEBPS/part2.xhtml", "<html><body><h1>Part Two</h1><p>The end.</p></body></html>"),
+        ]);
+
+        let unmerged = parse_epub(&data).unwrap();
+        assert_eq!(unmerged.chapters.len(), 4);
+
+        let config = ParseConfig {
+            merge_sequential_short_chapters: true,
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.len(), 2);
+        let first = &book.chapters[0];
+        assert_eq!(first.title, "Part One");
+        assert_eq!(first.index, 0);
+        assert_eq!(first.words.len(), 2 + 2 + 2 + SHORT_CHAPTER_WORDS as usize);
+        assert_eq!(first.stats.word_count, first.words.len() as u32);
+        assert_eq!(first.paragraphs.len(), 4);
+        assert_eq!(first.paragraphs[2].word_start_offset, 4);
+        assert_eq!(first.paragraph_words(2).unwrap()[0].text, "It");
+        // A short last chapter has nothing to merge into
+        assert_eq!(book.chapters[1].title, "Part Two");
+        assert_eq!(book.chapters[1].global_word_start, first.stats.word_count);
+        assert_eq!(book.stats.total_words, unmerged.stats.total_words);
+    }
+
+    #[test]
+    fn test_zip_bomb_limits() {
+        // 1 MB of spaces deflates to about 1 KB
+        let mut data = Vec::new();
+        {
+            let mut zip = zip::ZipWriter::new(Cursor::new(&mut data));
+            let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+            zip.start_file("META-INF/container.xml", options).unwrap();
+            zip.write_all(CONTAINER_XML.as_bytes()).unwrap();
+            zip.start_file("OEBPS/bomb.xhtml", options).unwrap();
+            zip.write_all(&vec![b' '; 1024 * 1024]).unwrap();
+            zip.finish().unwrap();
+        }
+
+        assert!(matches!(parse_epub(&data), Err(EpubError::SuspiciousCompression(r)) if r > 100.0));
+
+        let small_limit = ParseConfig {
+            max_uncompressed_size: 1024,
+            max_compression_ratio: f64::INFINITY,
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_epub_with_config(&data, &small_limit),
+            Err(EpubError::TooLarge(size)) if size == 1024 * 1024
+        ));
+        assert!(matches!(LazyBook::open(data, small_limit), Err(EpubError::TooLarge(_))));
+    }
+
+    #[test]
+    fn test_max_xhtml_bytes() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Big</dc:title></metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        let chapter = format!("<html><body><p>{}</p></body></html>", "word ".repeat(2000));
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", &chapter),
+        ]);
+        assert_eq!(parse_epub(&data).unwrap().stats.total_words, 2000);
+
+        let config = ParseConfig {
+            max_xhtml_bytes: 1024,
+            ..Default::default()
+        };
+        let size = chapter.len() as u64;
+        assert!(matches!(parse_epub_with_config(&data, &config), Err(EpubError::TooLarge(s)) if s == size));
+        assert!(matches!(LazyBook::open(data.clone(), config), Err(EpubError::TooLarge(_))));
+
+        // The limit also applies while reading, whatever the header says
+        let mut archive = ZipArchive::new(Cursor::new(&data[..])).unwrap();
+        assert!(matches!(read_file_with_limit(&mut archive, "OEBPS/c1.xhtml", 1024), Err(EpubError::TooLarge(s)) if s == size));
+        assert_eq!(read_file_with_limit(&mut archive, "OEBPS/c1.xhtml", size).unwrap().len() as u64, size);
+    }
+
+    #[test]
+    fn test_parse_from_path_and_reader() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(path).expect("Failed to read demo.epub");
+        let expected = parse_epub(&data).unwrap();
+
+        let from_path = parse_epub_from_path(path).unwrap();
+        let from_reader = parse_epub_from_reader(Cursor::new(&data)).unwrap();
+        for book in [&from_path, &from_reader] {
+            assert_eq!(book.metadata.title, expected.metadata.title);
+            assert_eq!(book.stats.total_words, expected.stats.total_words);
+        }
+
+        let config = ParseConfig {
+            max_chunk_chars: 20,
+            ..Default::default()
+        };
+        let from_path = parse_epub_from_path_with_config(path, &config).unwrap();
+        let from_reader = parse_epub_from_reader_with_config(Cursor::new(&data), &config).unwrap();
+        assert_eq!(from_path.stats.total_words, from_reader.stats.total_words);
+
+        assert!(matches!(parse_epub_from_path("/nonexistent.epub"), Err(EpubError::Io(_))));
+    }
+
+    #[test]
+    fn test_chapter_global_word_start() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub")).unwrap();
+        let book = parse_epub(&data).unwrap();
+
+        let mut expected = 0;
+        for (i, chapter) in book.chapters.iter().enumerate() {
+            assert_eq!(chapter.global_word_start, expected);
+            assert_eq!(chapter.global_word_start, book.stats.chapter_offsets[i]);
+            expected += chapter.words.len() as u32;
+        }
+
+        let last = book.chapters.last().unwrap();
+        let global = last.global_word_start + 1;
+        assert_eq!(book.local_word_index(global), Some((book.chapters.len() - 1, 1)));
+    }
+
+    #[test]
+    fn test_word_frequency_table_demo() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub"))
+            .expect("Failed to read demo.epub");
+        let book = parse_epub(&data).unwrap();
+
+        let table = book.word_frequency_table();
+        assert_eq!(table.len() as u32, book.unique_word_count());
+        assert!(table.keys().all(|w| !w.is_empty() && *w == w.to_lowercase()));
+
+        let mut from_chapters: HashMap<String, u32> = HashMap::new();
+        for chapter in &book.chapters {
+            for (word, count) in chapter.word_frequency_table() {
+                *from_chapters.entry(word).or_insert(0) += count;
+            }
+        }
+        assert_eq!(from_chapters, table);
+
+        let top = book.top_n_words(10);
+        assert_eq!(top.len(), 10.min(table.len()));
+        assert!(top.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(top[0].1, *table.values().max().unwrap());
+    }
+
+    #[test]
+    fn test_read_epub_metadata_matches_full_parse() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub"))
+            .expect("Failed to read demo.epub");
+        let metadata = read_epub_metadata(&data).unwrap();
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(metadata.title, book.metadata.title);
+        assert_eq!(metadata.author, book.metadata.author);
+    }
+
+    #[test]
+    fn test_lazy_book_loads_on_demand() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub"))
+            .expect("Failed to read demo.epub");
+        let book = parse_epub(&data).unwrap();
+
+        let mut lazy = LazyBook::open(data, ParseConfig::default()).unwrap();
+        assert_eq!(lazy.metadata.title, book.metadata.title);
+        assert!(lazy.cache.is_empty());
+
+        for chapter in &book.chapters {
+            let loaded = lazy.get_chapter(chapter.index as usize).unwrap();
+            assert_eq!(loaded.title, chapter.title);
+            assert_eq!(loaded.words.len(), chapter.words.len());
+        }
+        assert_eq!(lazy.cache.len(), book.chapters.len());
+        assert!(lazy.get_chapter(lazy.chapter_count()).is_err());
+    }
+
+    #[test]
+    fn test_lazy_book_global_word_start() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub"))
+            .expect("Failed to read demo.epub");
+        let book = parse_epub(&data).unwrap();
+        let last = book.chapters.last().unwrap();
+        assert!(last.global_word_start > 0);
+
+        let mut lazy = LazyBook::open(data, ParseConfig::default()).unwrap();
+        // Unknown until the chapters before it are loaded
+        assert_eq!(lazy.get_chapter(last.index as usize).unwrap().global_word_start, 0);
+        assert_eq!(lazy.global_word_start(last.index as usize), None);
+        assert_eq!(lazy.global_word_start(0), Some(0));
+
+        lazy.preload_range(0, last.index as usize);
+        assert_eq!(lazy.global_word_start(last.index as usize), Some(last.global_word_start));
+        for chapter in &book.chapters {
+            assert_eq!(lazy.get_chapter(chapter.index as usize).unwrap().global_word_start, chapter.global_word_start);
+        }
+    }
+
+    #[test]
+    fn test_lazy_book_preload_range() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub"))
+            .expect("Failed to read demo.epub");
+        let mut lazy = LazyBook::open(data, ParseConfig::default()).unwrap();
+        lazy.preload_range(0, 2);
+        assert!(!lazy.cache.is_empty());
+        assert!(lazy.cache.keys().all(|&i| i < 2));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_parse_epub_async_matches_sync() {
+        let data = std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub")).unwrap();
+        let sync_book = parse_epub(&data).unwrap();
+
+        let async_book = parse_epub_async(data.clone()).await.unwrap();
+        assert_eq!(async_book.metadata.title, sync_book.metadata.title);
+        assert_eq!(async_book.chapters.len(), sync_book.chapters.len());
+        assert_eq!(async_book.stats.total_words, sync_book.stats.total_words);
+
+        let configured = parse_epub_with_config_async(data, ParseConfig::default()).await.unwrap();
+        assert_eq!(configured.stats.total_words, sync_book.stats.total_words);
+    }
+
+    #[test]
+    fn test_parse_clock_value_ms() {
+        assert_eq!(parse_clock_value_ms("0:01:02.5"), Some(62_500));
+        assert_eq!(parse_clock_value_ms("02:03.25"), Some(123_250));
+        assert_eq!(parse_clock_value_ms("12.5s"), Some(12_500));
+        assert_eq!(parse_clock_value_ms("500ms"), Some(500));
+        assert_eq!(parse_clock_value_ms("1.5min"), Some(90_000));
+        assert_eq!(parse_clock_value_ms("2h"), Some(7_200_000));
+        assert_eq!(parse_clock_value_ms("3"), Some(3_000));
+        assert_eq!(parse_clock_value_ms("soon"), None);
+        assert_eq!(parse_clock_value_ms("-1s"), None);
+    }
+
+    #[test]
+    fn test_media_overlay_audio_duration() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Narrated</dc:title></metadata>
+  <manifest>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml" media-overlay="c1_smil"/>
+    <item id="c2" href="c2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1_smil" href="c1.smil" media-type="application/smil+xml"/>
+  </manifest>
+  <spine><itemref idref="c1"/><itemref idref="c2"/></spine>
+</package>"#;
+        let smil = r#"<smil xmlns="http://www.w3.org/ns/SMIL" version="3.0"><body><seq>
+  <par><text src="c1.xhtml#p1"/><audio src="c1.mp3" clipBegin="0:00:00.000" clipEnd="0:00:05.500"/></par>
+  <par><text src="c1.xhtml#p2"/><audio src="c1.mp3" clipBegin="5.5s" clipEnd="12s"/></par>
+  <par><text src="c1.xhtml#p3"/><audio src="c1.mp3" clipEnd="250ms"/></par>
+</seq></body></smil>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p id='p1'>One.</p><p id='p2'>Two.</p></body></html>"),
+            ("OEBPS/c2.xhtml", "<html><body><p>Silent.</p></body></html>"),
+            ("OEBPS/c1.smil", smil),
+        ]);
+
+        // Off by default
+        let book = parse_epub(&data).unwrap();
+        assert!(book.chapters.iter().all(|c| c.audio_duration_ms.is_none()));
+
+        let config = ParseConfig {
+            extract_audio_metadata: true,
+            ..Default::default()
+        };
+        let book = parse_epub_with_config(&data, &config).unwrap();
+        assert_eq!(book.chapters.len(), 2);
+        assert_eq!(book.chapters[0].audio_duration_ms, Some(12_250));
+        assert_eq!(book.chapters[1].audio_duration_ms, None);
+        assert_eq!(book.metadata.epub_version, EpubVersion::Epub3);
+
+        let mut lazy = LazyBook::open(data, config).unwrap();
+        assert_eq!(lazy.get_chapter(0).unwrap().audio_duration_ms, Some(12_250));
+    }
+
+    #[test]
+    fn test_parse_chapters_from_html_bytes() {
+        let files: [(&str, &[u8]); 4] = [
+            ("02.html", b"<html><body><p>Second <b>file<p>unclosed</body></html>"),
+            ("01.xhtml", b"<html><body><h1>Opening</h1><p>First file.</p></body></html>"),
+            ("00-cover.xhtml", b"<html><body><img src='cover.jpg'/></body></html>"),
+            ("03.xhtml", b"<html><body><p>Third.</p></body></html>"),
+        ];
+        let chapters = parse_chapters_from_html_bytes(&files).unwrap();
+
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters.iter().map(|c| c.index).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(chapters[0].title, "Opening");
+        assert_eq!(chapters[1].words[0].text, "Second");
+        assert_eq!(chapters[1].words.len(), 3);
+        assert_eq!(chapters[2].global_word_start, 3 + 3);
+
+        assert!(matches!(parse_chapters_from_html_bytes(&[]), Err(EpubError::InvalidStructure(_))));
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let not_zip = parse_epub(b"not a zip").unwrap_err();
+        assert_eq!(not_zip.error_code(), "epub/zip-error");
+        assert!(!not_zip.is_recoverable());
+
+        let no_container = parse_epub(&build_epub(&[("mimetype", "application/epub+zip")])).unwrap_err();
+        assert_eq!(no_container.error_code(), "epub/missing-container");
+        assert!(!no_container.is_recoverable());
+
+        let invalid = EpubError::InvalidStructure("File not found: x".to_string());
+        assert_eq!(invalid.error_code(), "epub/invalid-structure");
+        assert!(invalid.is_recoverable());
+        assert!(EpubError::Warning(ParseWarning::EmptyChapter(0)).is_recoverable());
+    }
+
+    /// Set the "encrypted" general-purpose flag on every entry, as a
+    /// password-protected export would (the data itself is left as is).
+    fn set_encrypted_flags(mut data: Vec<u8>) -> Vec<u8> {
+        for i in 0..data.len().saturating_sub(10) {
+            let flags_at = match &data[i..i + 4] {
+                b"PK\x03\x04" => i + 6, // local file header
+                b"PK\x01\x02" => i + 8, // central directory header
+                _ => continue,
+            };
+            data[flags_at] |= 1;
+        }
+        data
+    }
+
+    #[test]
+    fn test_export_epub_round_trip() {
+        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(epub_path).expect("Failed to read demo.epub");
+        let mut book = parse_epub(&data).unwrap();
+        book.metadata.additional_authors.push("Co <Author> & Friend".to_string());
+        book.metadata.series = Some("Speed".to_string());
+
+        let exported = export_epub(&book).unwrap();
+        let reparsed = parse_epub(&exported).unwrap();
+        assert_eq!(reparsed.metadata.title, book.metadata.title);
+        assert_eq!(reparsed.metadata.authors().collect::<Vec<_>>(), book.metadata.authors().collect::<Vec<_>>());
+        assert_eq!(reparsed.metadata.epub_version, EpubVersion::Epub2);
+        assert_eq!(reparsed.metadata.series.as_deref(), Some("Speed"));
+        assert_eq!(reparsed.stats.total_words, book.stats.total_words);
+        assert_eq!(reparsed.chapters.len(), book.chapters.len());
+        for (old, new) in book.chapters.iter().zip(&reparsed.chapters) {
+            assert_eq!(new.title, old.title);
+            let texts = |c: &Chapter| c.words.iter().map(|w| w.text.clone()).collect::<Vec<_>>();
+            assert_eq!(texts(new), texts(old));
+        }
+        assert!(reparsed.warnings.is_empty(), "{:?}", reparsed.warnings);
+        assert!(EpubValidator::validate(&exported).iter().all(|issue| issue.severity != Severity::Error));
+    }
+
+    #[test]
+    fn test_password_protected_epub() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Locked</dc:title></metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        let files = [
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Secret.</p></body></html>"),
+        ];
+        let data = set_encrypted_flags(build_epub(&files));
+
+        let err = parse_epub(&data).unwrap_err();
+        assert!(matches!(err, EpubError::Encrypted), "{:?}", err);
+        assert_eq!(err.error_code(), "epub/encrypted");
+        assert!(!err.is_recoverable());
+        assert!(matches!(LazyBook::open(data.clone(), ParseConfig::default()), Err(EpubError::Encrypted)));
+        assert!(matches!(read_epub_metadata(&data), Err(EpubError::Encrypted)));
+
+        let mut archive = ZipArchive::new(Cursor::new(&data[..])).unwrap();
+        assert!(matches!(read_file(&mut archive, "oebps/c1.xhtml"), Err(EpubError::Encrypted)));
+
+        assert!(parse_epub(&build_epub(&files)).is_ok());
+    }
+
+    #[test]
+    fn test_xml_files_with_bom() {
+        let opf = "\u{FEFF}<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"2.0\">
+  <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\"><dc:title>Windows Book</dc:title></metadata>
+  <manifest><item id=\"c1\" href=\"c1.xhtml\" media-type=\"application/xhtml+xml\"/></manifest>
+  <spine><itemref idref=\"c1\"/></spine>
+</package>";
+        let container = format!("\u{FEFF}{}", CONTAINER_XML);
+        let xhtml = "\u{FEFF}<?xml version=\"1.0\"?><html><body><p>Byte order.</p></body></html>";
+        let data = build_epub(&[
+            ("META-INF/container.xml", &container),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", xhtml),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.metadata.title, "Windows Book");
+        assert!(book.warnings.is_empty());
+        assert_eq!(book.chapters[0].words[0].text, "Byte");
+
+        let (text, error) = extract_text_from_xhtml(xhtml.as_bytes());
+        assert!(error.is_none());
+        assert_eq!(text, "Byte order.");
+    }
+
+    #[test]
+    fn test_ncx_in_spine_yields_no_words() {
+        let ncx = br#"<?xml version="1.0"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <docTitle><text>Book</text></docTitle>
+  <navMap><navPoint id="n1"><navLabel><text>Chapter One</text></navLabel><content src="c1.xhtml"/></navPoint></navMap>
+</ncx>"#;
+        let (text, error) = extract_text_from_xhtml(ncx);
+        assert_eq!(text, "");
+        assert!(error.is_none());
+        // Sniffed before any parse error, so there is no HTML fallback
+        assert_eq!(extract_text_from_xhtml(b"<ncx><navMap><text>Oops</navMap>").0, "");
+
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Mislabelled</dc:title></metadata>
+  <manifest>
+    <item id="toc" href="toc.ncx" media-type="application/xhtml+xml"/>
+    <item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine><itemref idref="toc"/><itemref idref="c1"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/toc.ncx", std::str::from_utf8(ncx).unwrap()),
+            ("OEBPS/c1.xhtml", "<html><body><p>Only text.</p></body></html>"),
+        ]);
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.stats.total_words, 2);
+        assert!(book.warnings.contains(&ParseWarning::EmptyChapter(0)));
+    }
+
+    #[test]
+    fn test_extract_text_namespaced_body() {
+        let xhtml = br#"<?xml version="1.0"?>
+<xhtml:html xmlns:xhtml="http://www.w3.org/1999/xhtml">
+  <xhtml:head><xhtml:title>Ignored</xhtml:title></xhtml:head>
+  <xhtml:body><xhtml:h1>Prefixed Title</xhtml:h1><xhtml:p>First paragraph.</xhtml:p><xhtml:p>Second<xhtml:br/>line.</xhtml:p></xhtml:body>
+</xhtml:html>"#;
+        let (text, error) = extract_text_from_xhtml(xhtml);
+        assert!(error.is_none());
+        assert_eq!(text, "Prefixed Title\n\nFirst paragraph.\n\nSecond\n\nline.");
+        assert_eq!(extract_title_from_xhtml(xhtml).as_deref(), Some("Prefixed Title"));
+    }
+
+    #[test]
+    fn test_read_opf_strips_namespace_prefixes() {
+        let opf = r#"<?xml version="1.0"?>
+<opf:package xmlns:opf="http://www.idpf.org/2007/opf" xmlns:dc="http://purl.org/dc/elements/1.1/" version="2.0">
+  <opf:metadata><dc:title>Prefixed</dc:title><dc:creator>Someone</dc:creator></opf:metadata>
+  <opf:manifest>
+    <opf:item opf:id="c1" opf:href="c1.xhtml" opf:media-type="application/xhtml+xml"/>
+  </opf:manifest>
+  <opf:spine><opf:itemref opf:idref="c1"/></opf:spine>
+</opf:package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Namespaced text.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.metadata.title, "Prefixed");
+        assert_eq!(book.metadata.author.as_deref(), Some("Someone"));
+        assert_eq!(book.metadata.epub_version, EpubVersion::Epub2);
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].words[0].text, "Namespaced");
+    }
+
+    #[test]
+    fn test_parse_series_and_creators() {
+        let opf = r##"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Oathbringer</dc:title>
+    <dc:creator>Brandon Sanderson</dc:creator>
+    <dc:creator>Isaac Stewart</dc:creator>
+    <meta property="belongs-to-collection" id="set">Cosmere</meta>
+    <meta refines="#set" property="collection-type">set</meta>
+    <meta property="belongs-to-collection" id="c1">The Stormlight Archive</meta>
+    <meta refines="#c1" property="collection-type">series</meta>
+    <meta refines="#c1" property="group-position">3.5</meta>
+  </metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"##;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.metadata.series.as_deref(), Some("The Stormlight Archive"));
+        assert_eq!(book.metadata.series_index, Some(3.5));
+        assert_eq!(book.metadata.author.as_deref(), Some("Brandon Sanderson"));
+        assert_eq!(book.metadata.additional_authors, vec!["Isaac Stewart"]);
+        assert_eq!(book.metadata.authors().collect::<Vec<_>>(), vec!["Brandon Sanderson", "Isaac Stewart"]);
+        assert!(book.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_parse_calibre_series() {
+        let series = |extra: &str| {
+            let opf = format!(
+                r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    <dc:title>Dune Messiah</dc:title>
+    <meta name="calibre:series" content="Dune"/>
+    <meta name="calibre:series_index" content="2.0"></meta>
+    {}
+  </metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#,
+                extra
+            );
+            let data = build_epub(&[
+                ("META-INF/container.xml", CONTAINER_XML),
+                ("OEBPS/content.opf", &opf),
+                ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+            ]);
+            let metadata = read_epub_metadata(&data).unwrap();
+            (metadata.series, metadata.series_index)
+        };
+
+        assert_eq!(series(""), (Some("Dune".to_string()), Some(2.0)));
+        // The EPUB 3 collection wins over Calibre's
+        let epub3 = r##"<meta property="belongs-to-collection" id="c1">Dune Chronicles</meta>
+    <meta refines="#c1" property="group-position">2</meta>"##;
+        assert_eq!(series(epub3), (Some("Dune Chronicles".to_string()), Some(2.0)));
+    }
+
+    #[test]
+    fn test_try_from_bytes() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(path).unwrap();
+
+        let from_slice: Book = data.as_slice().try_into().unwrap();
+        let from_vec = Book::try_from(data.clone()).unwrap();
+        assert_eq!(from_slice, parse_epub(&data).unwrap());
+        assert_eq!(from_vec, from_slice);
+        assert!(Book::try_from(&b"not a zip"[..]).is_err());
+    }
+
+    #[test]
+    fn test_unique_identifier() {
+        let opf = |package_attr: &str| {
+            format!(
+                r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" {}>
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Ids</dc:title>
+    <dc:identifier id="isbn">978-0-00-000000-2</dc:identifier>
+    <dc:identifier id="uuid_id"> urn:uuid:0b1c2d3e-0000-4000-8000-000000000000 </dc:identifier>
+  </metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#,
+                package_attr
+            )
+        };
+        let unique_id = |package_attr: &str| {
+            let opf = opf(package_attr);
+            let data = build_epub(&[
+                ("META-INF/container.xml", CONTAINER_XML),
+                ("OEBPS/content.opf", &opf),
+                ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+            ]);
+            read_epub_metadata(&data).unwrap().unique_id
+        };
+
+        assert_eq!(unique_id(r#"unique-identifier="uuid_id""#).as_deref(), Some("urn:uuid:0b1c2d3e-0000-4000-8000-000000000000"));
+        assert_eq!(unique_id(r#"unique-identifier="isbn""#).as_deref(), Some("978-0-00-000000-2"));
+        // A dangling or missing reference falls back to the first identifier
+        assert_eq!(unique_id(r#"unique-identifier="nope""#).as_deref(), Some("978-0-00-000000-2"));
+        assert_eq!(unique_id("").as_deref(), Some("978-0-00-000000-2"));
+    }
+
+    #[test]
+    fn test_parse_meta_properties() {
+        let opf = r##"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title id="t">The Meta Book</dc:title>
+    <meta property="dcterms:modified">2023-01-01T00:00:00Z</meta>
+    <meta property="schema:numberOfPages"> 412 </meta>
+    <meta property="schema:accessMode">textual</meta>
+    <meta refines="#t" property="title-type">main</meta>
+    <meta name="cover" content="cover-image"/>
+  </metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"##;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", "<html><body><p>Text.</p></body></html>"),
+        ]);
+
+        let metadata = parse_epub(&data).unwrap().metadata;
+        assert_eq!(metadata.modified_date.as_deref(), Some("2023-01-01T00:00:00Z"));
+        assert_eq!(metadata.page_count, Some(412));
+        assert_eq!(metadata.leading_article.as_deref(), Some("The"));
+        // Refinements and EPUB 2 name/content metas are not extra properties
+        assert_eq!(metadata.extra_meta.len(), 1);
+        assert_eq!(metadata.extra_meta["schema:accessMode"], "textual");
+        assert_eq!(read_epub_metadata(&data).unwrap(), metadata);
+    }
+
+    #[test]
+    fn test_percent_encoded_hrefs() {
+        assert_eq!(percent_decode("Chapter%201.xhtml"), "Chapter 1.xhtml");
+        assert_eq!(percent_decode("caf%C3%A9.xhtml"), "café.xhtml");
+        assert_eq!(percent_decode("100%.xhtml"), "100%.xhtml");
+        assert_eq!(percent_decode("bad%zz"), "bad%zz");
+
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Spaces</dc:title></metadata>
+  <manifest><item id="c1" href="Chapter%201.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/Chapter 1.xhtml", "<html><body><p>Spaced out.</p></body></html>"),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert_eq!(book.chapters.len(), 1);
+        assert_eq!(book.chapters[0].words[0].text, "Spaced");
+        assert!(book.warnings.is_empty());
+        assert!(!EpubValidator::validate(&data).iter().any(|i| i.code == "manifest-item-missing"));
+    }
+
+    #[test]
+    fn test_parse_falls_back_to_lenient_html() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Legacy</dc:title></metadata>
+  <manifest><item id="c1" href="c1.html" media-type="text/html"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        let chapter = "<html><body><p>One<br>two</i> three<p>Four five</body></html>";
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.html", chapter),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        let texts: Vec<&str> = book.chapters[0].words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["One", "two", "three", "Four", "five"]);
+        assert!(matches!(book.warnings[0], ParseWarning::MalformedXhtml(_)));
+    }
+
+    #[test]
+    fn test_footnotes_excluded_from_text() {
+        let html = br##"<html xmlns:epub="http://www.idpf.org/2007/ops"><body>
+            <p>Main text<a epub:type="noteref" href="#n1">1</a> continues.</p>
+            <aside epub:type="footnote" id="n1"><p>A <em>nested</em> note.</p></aside>
+            <p>More text.</p>
+            <aside epub:type="rearnote endnote" id="n2">End note.</aside>
+            </body></html>"##;
+
+        let (text, _) = extract_text_from_xhtml(html);
+        assert!(text.contains("Main text"));
+        assert!(text.contains("More text."));
+        assert!(!text.contains("note."));
+
+        let notes = extract_footnotes(html);
+        assert_eq!(
+            notes,
+            vec![
+                Footnote { id: "n1".to_string(), text: "A nested note.".to_string() },
+                Footnote { id: "n2".to_string(), text: "End note.".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_structure_errors() {
+        let container = |rootfiles: &str| {
+            format!(
+                r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container"><rootfiles>{}</rootfiles></container>"#,
+                rootfiles
+            )
+        };
+
+        let no_rootfile = build_epub(&[("META-INF/container.xml", &container(""))]);
+        assert!(matches!(parse_epub(&no_rootfile), Err(EpubError::MissingOpf)));
+
+        let missing_opf = build_epub(&[("META-INF/container.xml", CONTAINER_XML)]);
+        match parse_epub(&missing_opf) {
+            Err(EpubError::InvalidStructure(msg)) => assert_eq!(msg, "File not found: OEBPS/content.opf"),
+            other => panic!("expected InvalidStructure, got {:?}", other.map(|_| ())),
+        }
+
+        let broken = build_epub(&[("META-INF/container.xml", &container("<rootfile></rootfiles>"))]);
+        assert!(matches!(parse_epub(&broken), Err(EpubError::Xml(_))));
+    }
+
+    #[test]
+    fn test_footnotes_with_other_prefix() {
+        let html = br##"<html xmlns:ops="http://www.idpf.org/2007/ops" xmlns:x="urn:other"><body>
+            <p>Main text.</p>
+            <aside ops:type="footnote" id="n1">Prefixed note.</aside>
+            <aside x:type="footnote" id="n2">Not a note.</aside>
+            </body></html>"##;
+
+        let (text, _) = extract_text_from_xhtml(html);
+        assert_eq!(text, "Main text. Not a note.");
+        let ids: Vec<String> = extract_footnotes(html).into_iter().map(|note| note.id).collect();
+        assert_eq!(ids, vec!["n1"]);
+    }
+
+    #[test]
+    fn test_footnotes_excluded_from_malformed_xhtml() {
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Notes</dc:title></metadata>
+  <manifest><item id="c1" href="c1.xhtml" media-type="application/xhtml+xml"/></manifest>
+  <spine><itemref idref="c1"/></spine>
+</package>"#;
+        // Unclosed <br> makes this fall back to the lenient HTML path
+        let xhtml = r##"<html xmlns:epub="http://www.idpf.org/2007/ops"><body>
+            <p>Main text<br>continues.</p>
+            <aside epub:type="footnote" id="n1"><div>Hidden <div>nested</div> note.</div></aside>
+            <p>More text.</p>
+            </body></html>"##;
+        let data = build_epub(&[
+            ("META-INF/container.xml", CONTAINER_XML),
+            ("OEBPS/content.opf", opf),
+            ("OEBPS/c1.xhtml", xhtml),
+        ]);
+
+        let book = parse_epub(&data).unwrap();
+        assert!(matches!(book.warnings[0], ParseWarning::MalformedXhtml(_)));
+        let texts: Vec<&str> = book.chapters[0].words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Main", "text", "continues.", "More", "text."]);
+    }
+
+    #[test]
+    fn test_extract_text_strips_ruby_annotations() {
+        let html = "<html><body><p>私は<ruby>日本<rt>にほん</rt>語<rt>ご</rt></ruby>を話す。</p></body></html>";
+        let (text, _) = extract_text_from_xhtml(html.as_bytes());
+        assert_eq!(text, "私は 日本語 を話す。");
+    }
 }
@@ -2,11 +2,172 @@
 //!
 //! These functions are called from Kotlin via JNI.
 
-use crate::epub::{parse_epub, parse_epub_with_config};
-use crate::types::{Book, Chapter, ChapterStats, Word};
-use jni::objects::{JByteArray, JClass, JObject, JString, JValue};
-use jni::sys::{jobject, jstring};
-use jni::JNIEnv;
+use crate::epub::{parse_epub, parse_epub_with_config, ParseConfig};
+use crate::types::{Book, BookMetadata, BookStats, Chapter, ChapterStats, EpubVersion, Word};
+use jni::objects::{GlobalRef, JByteArray, JClass, JIntArray, JMethodID, JObject, JString, JValue};
+use jni::sys::{jfloat, jint, jobject, jstring, jvalue, JNI_ERR, JNI_VERSION_1_6, JNI_VERSION_1_8};
+use jni::{JNIEnv, JavaVM};
+use std::ffi::c_void;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Class and constructor references for the Java DTOs, looked up once.
+///
+/// `find_class` walks the class loader on every call, and a book makes one
+/// call per word, so the lookups are done at load time instead.
+struct JniCache {
+    book_class: GlobalRef,
+    book_ctor: JMethodID,
+    metadata_class: GlobalRef,
+    metadata_ctor: JMethodID,
+    chapter_class: GlobalRef,
+    chapter_ctor: JMethodID,
+    word_class: GlobalRef,
+    word_ctor: JMethodID,
+    chapter_stats_class: GlobalRef,
+    chapter_stats_ctor: JMethodID,
+    book_stats_class: GlobalRef,
+    book_stats_ctor: JMethodID,
+    float_class: GlobalRef,
+    float_ctor: JMethodID,
+    /// Element class of the metadata's `String[]` authors
+    string_class: GlobalRef,
+    book_flat_class: GlobalRef,
+    book_flat_ctor: JMethodID,
+    chapter_flat_class: GlobalRef,
+    chapter_flat_ctor: JMethodID,
+}
+
+/// Filled by `JNI_OnLoad` (or lazily by the first call) and emptied by
+/// `JNI_OnUnload`, which is why this isn't a `OnceLock`.
+static JNI_CACHE: RwLock<Option<Arc<JniCache>>> = RwLock::new(None);
+
+impl JniCache {
+    fn new(env: &mut JNIEnv) -> Result<Self, jni::errors::Error> {
+        let (book_class, book_ctor) = lookup_class(
+            env,
+            "app/spread/data/NativeBook",
+            "(Lapp/spread/data/NativeBookMetadata;[Lapp/spread/data/NativeChapter;Lapp/spread/data/NativeBookStats;)V",
+        )?;
+        let (metadata_class, metadata_ctor) = lookup_class(
+            env,
+            "app/spread/data/NativeBookMetadata",
+            "(Ljava/lang/String;[Ljava/lang/String;Ljava/lang/String;Ljava/lang/Float;I)V",
+        )?;
+        let (chapter_class, chapter_ctor) = lookup_class(
+            env,
+            "app/spread/data/NativeChapter",
+            "(ILjava/lang/String;[Lapp/spread/data/NativeWord;Lapp/spread/data/NativeChapterStats;)V",
+        )?;
+        let (word_class, word_ctor) =
+            lookup_class(env, "app/spread/data/NativeWord", "(Ljava/lang/String;II)V")?;
+        let (chapter_stats_class, chapter_stats_ctor) =
+            lookup_class(env, "app/spread/data/NativeChapterStats", "(I[I[I)V")?;
+        // SYNC: Must match the NativeBookStats constructor in Kotlin NativeParser.kt
+        let (book_stats_class, book_stats_ctor) = lookup_class(
+            env,
+            "app/spread/data/NativeBookStats",
+            "(ILapp/spread/data/NativeChapterStats;[I)V",
+        )?;
+        let (float_class, float_ctor) = lookup_class(env, "java/lang/Float", "(F)V")?;
+        let string_class = env.find_class("java/lang/String")?;
+        let string_class = env.new_global_ref(string_class)?;
+        let (book_flat_class, book_flat_ctor) = lookup_class(
+            env,
+            "app/spread/data/NativeBookFlat",
+            "(Lapp/spread/data/NativeBookMetadata;[Lapp/spread/data/NativeChapterFlat;Lapp/spread/data/NativeBookStats;[B[I)V",
+        )?;
+        let (chapter_flat_class, chapter_flat_ctor) = lookup_class(
+            env,
+            "app/spread/data/NativeChapterFlat",
+            "(ILjava/lang/String;IILapp/spread/data/NativeChapterStats;)V",
+        )?;
+
+        Ok(JniCache {
+            book_class,
+            book_ctor,
+            metadata_class,
+            metadata_ctor,
+            chapter_class,
+            chapter_ctor,
+            word_class,
+            word_ctor,
+            chapter_stats_class,
+            chapter_stats_ctor,
+            book_stats_class,
+            book_stats_ctor,
+            float_class,
+            float_ctor,
+            string_class,
+            book_flat_class,
+            book_flat_ctor,
+            chapter_flat_class,
+            chapter_flat_ctor,
+        })
+    }
+
+    /// The cache, filling it from `env` if `JNI_OnLoad` didn't run.
+    fn get(env: &mut JNIEnv) -> Result<Arc<JniCache>, jni::errors::Error> {
+        if let Some(cache) = JNI_CACHE.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+            return Ok(Arc::clone(cache));
+        }
+        let cache = Arc::new(JniCache::new(env)?);
+        let mut slot = JNI_CACHE.write().unwrap_or_else(PoisonError::into_inner);
+        Ok(Arc::clone(slot.get_or_insert(cache)))
+    }
+}
+
+fn lookup_class(
+    env: &mut JNIEnv,
+    name: &str,
+    ctor_sig: &str,
+) -> Result<(GlobalRef, JMethodID), jni::errors::Error> {
+    let class = env.find_class(name)?;
+    let ctor = env.get_method_id(&class, "<init>", ctor_sig)?;
+    Ok((env.new_global_ref(class)?, ctor))
+}
+
+/// Construct an object from a cached class and constructor.
+fn new_cached_object<'local>(
+    env: &mut JNIEnv<'local>,
+    class: &GlobalRef,
+    ctor: JMethodID,
+    args: &[jvalue],
+) -> Result<JObject<'local>, jni::errors::Error> {
+    let class: &JClass = class.as_obj().into();
+    // SAFETY: every cached `ctor` was looked up on its own `class` with the
+    // signature the callers build `args` for.
+    unsafe { env.new_object_unchecked(class, ctor, args) }
+}
+
+/// Called by the JVM when the library is loaded; checks the JNI version and
+/// fills the class cache while the app's class loader is on the stack.
+///
+/// Returns `JNI_VERSION_1_8` on JVMs that support it. Android's ART reports
+/// (and only accepts) 1.6, which is all these bindings need.
+#[no_mangle]
+pub extern "system" fn JNI_OnLoad(vm: JavaVM, _reserved: *mut c_void) -> jint {
+    let Ok(mut env) = vm.get_env() else {
+        return JNI_ERR;
+    };
+    let version = match env.get_version() {
+        Ok(version) => jint::from(version),
+        Err(_) => return JNI_ERR,
+    };
+    if version < JNI_VERSION_1_6 {
+        return JNI_ERR;
+    }
+    match JniCache::get(&mut env) {
+        Ok(_) => version.min(JNI_VERSION_1_8),
+        Err(_) => JNI_ERR,
+    }
+}
+
+/// Called when the class loader that loaded the library is collected;
+/// releases the cached global class references.
+#[no_mangle]
+pub extern "system" fn JNI_OnUnload(_vm: JavaVM, _reserved: *mut c_void) {
+    JNI_CACHE.write().unwrap_or_else(PoisonError::into_inner).take();
+}
 
 /// Parse an EPUB file and return a Book object.
 ///
@@ -57,7 +218,7 @@ pub extern "system" fn Java_app_spread_data_NativeParser_parseEpubWithConfig<'lo
     };
 
     // Parse EPUB with config
-    let book = match parse_epub_with_config(&data_vec, max_chunk_chars as usize) {
+    let book = match parse_epub_with_config(&data_vec, &config_with_max_chunk_chars(max_chunk_chars)) {
         Ok(b) => b,
         Err(e) => {
             eprintln!("EPUB parse error: {}", e);
@@ -72,6 +233,164 @@ pub extern "system" fn Java_app_spread_data_NativeParser_parseEpubWithConfig<'lo
     }
 }
 
+/// Parse an EPUB into a `NativeBookFlat`: the words of all chapters packed
+/// into one `int[]` plus a UTF-8 text pool, so the JVM allocates a few
+/// objects per chapter instead of one per word.
+///
+/// Kotlin signature: external fun parseEpubFlat(data: ByteArray, maxChunkChars: Int): NativeBookFlat?
+#[no_mangle]
+pub extern "system" fn Java_app_spread_data_NativeParser_parseEpubFlat<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    data: JByteArray<'local>,
+    max_chunk_chars: jint,
+) -> jobject {
+    let data_vec = match env.convert_byte_array(&data) {
+        Ok(v) => v,
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    let book = match parse_epub_with_config(&data_vec, &config_with_max_chunk_chars(max_chunk_chars)) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("EPUB parse error: {}", e);
+            return std::ptr::null_mut();
+        }
+    };
+
+    match book_to_flat_jobject(&mut env, &book) {
+        Ok(obj) => obj.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+fn config_with_max_chunk_chars(max_chunk_chars: jint) -> ParseConfig {
+    ParseConfig {
+        max_chunk_chars: max_chunk_chars as usize,
+        ..Default::default()
+    }
+}
+
+/// Fraction of the book (0.0-1.0) read at a global word offset.
+///
+/// Kotlin signature: external fun readingProgress(totalWords: Int, wordOffset: Int): Float
+#[no_mangle]
+pub extern "system" fn Java_app_spread_data_NativeParser_readingProgress<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    total_words: jint,
+    word_offset: jint,
+) -> jfloat {
+    let stats = BookStats {
+        total_words: total_words.max(0) as u32,
+        ..Default::default()
+    };
+    stats.reading_progress(word_offset.max(0) as u32)
+}
+
+/// Nearest global word offset for a progress fraction.
+///
+/// Kotlin signature: external fun wordOffsetForProgress(totalWords: Int, fraction: Float): Int
+#[no_mangle]
+pub extern "system" fn Java_app_spread_data_NativeParser_wordOffsetForProgress<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    total_words: jint,
+    fraction: jfloat,
+) -> jint {
+    let stats = BookStats {
+        total_words: total_words.max(0) as u32,
+        ..Default::default()
+    };
+    stats.word_offset_for_progress(fraction) as jint
+}
+
+/// Global word offset of a (chapter, word) position, given each chapter's
+/// word count (`NativeBookStats.chapterWordCounts`). Returns -1 if the
+/// array can't be read or holds a negative count, or the counts overflow.
+///
+/// Positions count the same words as the counts: real words, which for
+/// books parsed through these bindings (no pause words) is also the index
+/// into the chapter's `words`. A word past the end of its chapter is
+/// clamped to the chapter's end; a chapter past the last gives the total.
+///
+/// Kotlin signature: external fun positionToOffset(chapterWordCounts: IntArray, chapter: Int, word: Int): Int
+#[no_mangle]
+pub extern "system" fn Java_app_spread_data_NativeParser_positionToOffset<'local>(
+    env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    chapter_word_counts: JIntArray<'local>,
+    chapter: jint,
+    word: jint,
+) -> jint {
+    let Some(counts) = read_chapter_word_counts(&env, &chapter_word_counts) else {
+        return -1;
+    };
+    position_to_offset(&counts, chapter.max(0) as usize, word.max(0) as u32) as jint
+}
+
+/// Inverse of `positionToOffset`: the (chapter, word) position of a global
+/// word offset as a 2-element `int[]`, or null if the counts are invalid
+/// as for `positionToOffset`. Offsets past the end give the end of the last
+/// chapter.
+///
+/// Kotlin signature: external fun offsetToPosition(chapterWordCounts: IntArray, offset: Int): IntArray?
+#[no_mangle]
+pub extern "system" fn Java_app_spread_data_NativeParser_offsetToPosition<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    chapter_word_counts: JIntArray<'local>,
+    offset: jint,
+) -> jobject {
+    let Some(counts) = read_chapter_word_counts(&env, &chapter_word_counts) else {
+        return std::ptr::null_mut();
+    };
+    let (chapter, word) = offset_to_position(&counts, offset.max(0) as u32);
+    match int_array_to_jobject(&mut env, &[chapter, word]) {
+        Ok(obj) => obj.into_raw(),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Per-chapter word counts from Kotlin; `None` if unreadable.
+fn read_chapter_word_counts(env: &JNIEnv, counts: &JIntArray) -> Option<Vec<u32>> {
+    let mut values = vec![0; env.get_array_length(counts).ok()? as usize];
+    env.get_int_array_region(counts, 0, &mut values).ok()?;
+    chapter_word_counts(&values)
+}
+
+/// `values` as word counts; `None` if one is negative or they add up to
+/// more than `u32::MAX`, so sums over them can't overflow.
+fn chapter_word_counts(values: &[i32]) -> Option<Vec<u32>> {
+    let counts: Vec<u32> = values.iter().map(|&count| u32::try_from(count).ok()).collect::<Option<_>>()?;
+    counts.iter().try_fold(0u32, |total, &count| total.checked_add(count))?;
+    Some(counts)
+}
+
+/// See `Java_app_spread_data_NativeParser_positionToOffset`.
+fn position_to_offset(counts: &[u32], chapter: usize, word: u32) -> u32 {
+    let start: u32 = counts.iter().take(chapter).sum();
+    match counts.get(chapter) {
+        Some(&count) => start + word.min(count),
+        None => start,
+    }
+}
+
+/// See `Java_app_spread_data_NativeParser_offsetToPosition`.
+fn offset_to_position(counts: &[u32], offset: u32) -> (u32, u32) {
+    let mut start = 0;
+    for (chapter, &count) in counts.iter().enumerate() {
+        if offset - start < count {
+            return (chapter as u32, offset - start);
+        }
+        start += count;
+    }
+    match counts.len().checked_sub(1) {
+        Some(last) => (last as u32, counts[last]),
+        None => (0, 0),
+    }
+}
+
 /// Get parser version for debugging
 #[no_mangle]
 pub extern "system" fn Java_app_spread_data_NativeParser_getVersion<'local>(
@@ -88,76 +407,181 @@ fn book_to_jobject<'local>(
     env: &mut JNIEnv<'local>,
     book: &Book,
 ) -> Result<JObject<'local>, jni::errors::Error> {
-    // Create BookMetadata
-    let title = env.new_string(&book.metadata.title)?;
-    let author = match &book.metadata.author {
-        Some(a) => env.new_string(a)?,
-        None => JString::default(),
-    };
+    let cache = JniCache::get(env)?;
 
-    let metadata_class = env.find_class("app/spread/data/NativeBookMetadata")?;
-    let metadata = env.new_object(
-        metadata_class,
-        "(Ljava/lang/String;Ljava/lang/String;)V",
-        &[JValue::Object(&title), JValue::Object(&author)],
-    )?;
+    let metadata = metadata_to_jobject(env, &cache, &book.metadata)?;
 
     // Create chapters array
-    let chapter_class = env.find_class("app/spread/data/NativeChapter")?;
+    let chapter_class: &JClass = cache.chapter_class.as_obj().into();
     let chapters_array =
-        env.new_object_array(book.chapters.len() as i32, &chapter_class, JObject::null())?;
+        env.new_object_array(book.chapters.len() as i32, chapter_class, JObject::null())?;
 
     for (i, chapter) in book.chapters.iter().enumerate() {
-        let chapter_obj = chapter_to_jobject(env, chapter)?;
+        let chapter_obj = chapter_to_jobject(env, &cache, chapter)?;
         env.set_object_array_element(&chapters_array, i as i32, chapter_obj)?;
     }
 
     // Create BookStats
-    let stats = stats_to_jobject(env, &book.stats.aggregated, book.stats.total_words)?;
+    let stats = stats_to_jobject(env, &cache, &book.stats)?;
 
     // Create Book
-    let book_class = env.find_class("app/spread/data/NativeBook")?;
-    let book_obj = env.new_object(
-        book_class,
-        "(Lapp/spread/data/NativeBookMetadata;[Lapp/spread/data/NativeChapter;Lapp/spread/data/NativeBookStats;)V",
+    let book_obj = new_cached_object(
+        env,
+        &cache.book_class,
+        cache.book_ctor,
         &[
-            JValue::Object(&metadata),
-            JValue::Object(&chapters_array),
-            JValue::Object(&stats),
+            JValue::Object(&metadata).as_jni(),
+            JValue::Object(&chapters_array).as_jni(),
+            JValue::Object(&stats).as_jni(),
         ],
     )?;
 
     Ok(book_obj)
 }
 
+fn metadata_to_jobject<'local>(
+    env: &mut JNIEnv<'local>,
+    cache: &JniCache,
+    metadata: &BookMetadata,
+) -> Result<JObject<'local>, jni::errors::Error> {
+    let title = env.new_string(&metadata.title)?;
+    let author_count = metadata.authors().count() as i32;
+    let string_class: &JClass = cache.string_class.as_obj().into();
+    let authors = env.new_object_array(author_count, string_class, JObject::null())?;
+    for (i, author) in metadata.authors().enumerate() {
+        let author = env.new_string(author)?;
+        env.set_object_array_element(&authors, i as i32, author)?;
+    }
+
+    let series = match &metadata.series {
+        Some(s) => env.new_string(s)?,
+        None => JString::default(),
+    };
+    let series_index = match metadata.series_index {
+        Some(i) => new_cached_object(env, &cache.float_class, cache.float_ctor, &[JValue::Float(i).as_jni()])?,
+        None => JObject::null(),
+    };
+    let epub_version = match metadata.epub_version {
+        EpubVersion::Epub2 => 2,
+        EpubVersion::Epub3 => 3,
+        EpubVersion::Unknown(_) => 0,
+    };
+
+    new_cached_object(
+        env,
+        &cache.metadata_class,
+        cache.metadata_ctor,
+        &[
+            JValue::Object(&title).as_jni(),
+            JValue::Object(&authors).as_jni(),
+            JValue::Object(&series).as_jni(),
+            JValue::Object(&series_index).as_jni(),
+            JValue::Int(epub_version).as_jni(),
+        ],
+    )
+}
+
+fn book_to_flat_jobject<'local>(
+    env: &mut JNIEnv<'local>,
+    book: &Book,
+) -> Result<JObject<'local>, jni::errors::Error> {
+    let cache = JniCache::get(env)?;
+    let metadata = metadata_to_jobject(env, &cache, &book.metadata)?;
+
+    let chapter_class: &JClass = cache.chapter_flat_class.as_obj().into();
+    let chapters_array = env.new_object_array(book.chapters.len() as i32, chapter_class, JObject::null())?;
+    let mut word_start = 0;
+    for (i, chapter) in book.chapters.iter().enumerate() {
+        let title = env.new_string(&chapter.title)?;
+        let stats = chapter_stats_to_jobject(env, &cache, &chapter.stats)?;
+        let chapter_obj = new_cached_object(
+            env,
+            &cache.chapter_flat_class,
+            cache.chapter_flat_ctor,
+            &[
+                JValue::Int(chapter.index as i32).as_jni(),
+                JValue::Object(&title).as_jni(),
+                JValue::Int(word_start).as_jni(),
+                JValue::Int(chapter.words.len() as i32).as_jni(),
+                JValue::Object(&stats).as_jni(),
+            ],
+        )?;
+        env.set_object_array_element(&chapters_array, i as i32, chapter_obj)?;
+        word_start += chapter.words.len() as i32;
+    }
+
+    let (pool, words) = flatten_words(&book.chapters);
+    let text_pool = env.byte_array_from_slice(&pool)?;
+    let words_array = env.new_int_array(words.len() as i32)?;
+    env.set_int_array_region(&words_array, 0, &words)?;
+
+    let stats = stats_to_jobject(env, &cache, &book.stats)?;
+
+    new_cached_object(
+        env,
+        &cache.book_flat_class,
+        cache.book_flat_ctor,
+        &[
+            JValue::Object(&metadata).as_jni(),
+            JValue::Object(&chapters_array).as_jni(),
+            JValue::Object(&stats).as_jni(),
+            JValue::Object(&text_pool).as_jni(),
+            JValue::Object(&words_array).as_jni(),
+        ],
+    )
+}
+
+/// Ints per word in `NativeBookFlat.words`
+/// SYNC: Must match FLAT_WORD_FIELDS in Kotlin NativeParser.kt
+const FLAT_WORD_FIELDS: usize = 4;
+
+/// Every word of every chapter, in order, as a UTF-8 text pool plus
+/// `(pool offset, byte length, length bucket, punctuation)` per word.
+fn flatten_words(chapters: &[Chapter]) -> (Vec<u8>, Vec<i32>) {
+    let word_count: usize = chapters.iter().map(|c| c.words.len()).sum();
+    let mut pool = Vec::new();
+    let mut words = Vec::with_capacity(word_count * FLAT_WORD_FIELDS);
+    for word in chapters.iter().flat_map(|c| &c.words) {
+        words.extend([
+            pool.len() as i32,
+            word.text.len() as i32,
+            word.length_bucket as i32,
+            word.following_punct as i32,
+        ]);
+        pool.extend_from_slice(word.text.as_bytes());
+    }
+    (pool, words)
+}
+
 fn chapter_to_jobject<'local>(
     env: &mut JNIEnv<'local>,
+    cache: &JniCache,
     chapter: &Chapter,
 ) -> Result<JObject<'local>, jni::errors::Error> {
     let title = env.new_string(&chapter.title)?;
 
     // Create words array
-    let word_class = env.find_class("app/spread/data/NativeWord")?;
+    let word_class: &JClass = cache.word_class.as_obj().into();
     let words_array =
-        env.new_object_array(chapter.words.len() as i32, &word_class, JObject::null())?;
+        env.new_object_array(chapter.words.len() as i32, word_class, JObject::null())?;
 
     for (i, word) in chapter.words.iter().enumerate() {
-        let word_obj = word_to_jobject(env, word)?;
+        let word_obj = word_to_jobject(env, cache, word)?;
         env.set_object_array_element(&words_array, i as i32, word_obj)?;
     }
 
     // Create stats
-    let stats = chapter_stats_to_jobject(env, &chapter.stats)?;
+    let stats = chapter_stats_to_jobject(env, cache, &chapter.stats)?;
 
-    let chapter_class = env.find_class("app/spread/data/NativeChapter")?;
-    let chapter_obj = env.new_object(
-        chapter_class,
-        "(ILjava/lang/String;[Lapp/spread/data/NativeWord;Lapp/spread/data/NativeChapterStats;)V",
+    let chapter_obj = new_cached_object(
+        env,
+        &cache.chapter_class,
+        cache.chapter_ctor,
         &[
-            JValue::Int(chapter.index as i32),
-            JValue::Object(&title),
-            JValue::Object(&words_array),
-            JValue::Object(&stats),
+            JValue::Int(chapter.index as i32).as_jni(),
+            JValue::Object(&title).as_jni(),
+            JValue::Object(&words_array).as_jni(),
+            JValue::Object(&stats).as_jni(),
         ],
     )?;
 
@@ -166,18 +590,19 @@ fn chapter_to_jobject<'local>(
 
 fn word_to_jobject<'local>(
     env: &mut JNIEnv<'local>,
+    cache: &JniCache,
     word: &Word,
 ) -> Result<JObject<'local>, jni::errors::Error> {
     let text = env.new_string(&word.text)?;
 
-    let word_class = env.find_class("app/spread/data/NativeWord")?;
-    let word_obj = env.new_object(
-        word_class,
-        "(Ljava/lang/String;II)V",
+    let word_obj = new_cached_object(
+        env,
+        &cache.word_class,
+        cache.word_ctor,
         &[
-            JValue::Object(&text),
-            JValue::Int(word.length_bucket as i32),
-            JValue::Int(word.following_punct as i32),
+            JValue::Object(&text).as_jni(),
+            JValue::Int(word.length_bucket as i32).as_jni(),
+            JValue::Int(word.following_punct as i32).as_jni(),
         ],
     )?;
 
@@ -186,20 +611,21 @@ fn word_to_jobject<'local>(
 
 fn chapter_stats_to_jobject<'local>(
     env: &mut JNIEnv<'local>,
+    cache: &JniCache,
     stats: &ChapterStats,
 ) -> Result<JObject<'local>, jni::errors::Error> {
     // Create arrays first to avoid borrow checker issues
     let length_arr = int_array_to_jobject(env, &stats.length_counts)?;
     let punct_arr = int_array_to_jobject(env, &stats.punct_counts)?;
 
-    let stats_class = env.find_class("app/spread/data/NativeChapterStats")?;
-    let stats_obj = env.new_object(
-        stats_class,
-        "(I[I[I)V",
+    let stats_obj = new_cached_object(
+        env,
+        &cache.chapter_stats_class,
+        cache.chapter_stats_ctor,
         &[
-            JValue::Int(stats.word_count as i32),
-            JValue::Object(&length_arr),
-            JValue::Object(&punct_arr),
+            JValue::Int(stats.word_count as i32).as_jni(),
+            JValue::Object(&length_arr).as_jni(),
+            JValue::Object(&punct_arr).as_jni(),
         ],
     )?;
 
@@ -208,18 +634,20 @@ fn chapter_stats_to_jobject<'local>(
 
 fn stats_to_jobject<'local>(
     env: &mut JNIEnv<'local>,
-    stats: &ChapterStats,
-    total_words: u32,
+    cache: &JniCache,
+    stats: &BookStats,
 ) -> Result<JObject<'local>, jni::errors::Error> {
-    let chapter_stats = chapter_stats_to_jobject(env, stats)?;
+    let chapter_stats = chapter_stats_to_jobject(env, cache, &stats.aggregated)?;
+    let chapter_word_counts = int_array_to_jobject(env, &stats.chapter_word_counts())?;
 
-    let stats_class = env.find_class("app/spread/data/NativeBookStats")?;
-    let stats_obj = env.new_object(
-        stats_class,
-        "(ILapp/spread/data/NativeChapterStats;)V",
+    let stats_obj = new_cached_object(
+        env,
+        &cache.book_stats_class,
+        cache.book_stats_ctor,
         &[
-            JValue::Int(total_words as i32),
-            JValue::Object(&chapter_stats),
+            JValue::Int(stats.total_words as i32).as_jni(),
+            JValue::Object(&chapter_stats).as_jni(),
+            JValue::Object(&chapter_word_counts).as_jni(),
         ],
     )?;
 
@@ -228,10 +656,71 @@ fn stats_to_jobject<'local>(
 
 fn int_array_to_jobject<'local>(
     env: &mut JNIEnv<'local>,
-    arr: &[u32; 4],
+    arr: &[u32],
 ) -> Result<JObject<'local>, jni::errors::Error> {
-    let int_arr = env.new_int_array(4)?;
-    let vals: [i32; 4] = [arr[0] as i32, arr[1] as i32, arr[2] as i32, arr[3] as i32];
+    let int_arr = env.new_int_array(arr.len() as i32)?;
+    let vals: Vec<i32> = arr.iter().map(|&v| v as i32).collect();
     env.set_int_array_region(&int_arr, 0, &vals)?;
     Ok(JObject::from(int_arr))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::create_chapter;
+
+    #[test]
+    fn test_flatten_words() {
+        let chapters = vec![
+            create_chapter(0, "One".to_string(), &["Héllo, world."]),
+            create_chapter(1, "Two".to_string(), &["Again"]),
+        ];
+        let (pool, words) = flatten_words(&chapters);
+
+        assert_eq!(words.len(), 3 * FLAT_WORD_FIELDS);
+        let text = |i: usize| {
+            let (offset, len) = (words[i * FLAT_WORD_FIELDS] as usize, words[i * FLAT_WORD_FIELDS + 1] as usize);
+            std::str::from_utf8(&pool[offset..offset + len]).unwrap()
+        };
+        assert_eq!((text(0), text(1), text(2)), ("Héllo,", "world.", "Again"));
+        let word = &chapters[0].words[0];
+        assert_eq!(words[2], word.length_bucket as i32);
+        assert_eq!(words[3], word.following_punct as i32);
+        assert_eq!(pool.len(), "Héllo,world.Again".len());
+    }
+
+    #[test]
+    fn test_flat_word_fields_match_kotlin() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/kotlin/app/spread/data/NativeParser.kt");
+        let source = std::fs::read_to_string(path).expect("Failed to read NativeParser.kt");
+        let pattern = "const val FLAT_WORD_FIELDS = ";
+        let start = source.find(pattern).expect("FLAT_WORD_FIELDS not in NativeParser.kt") + pattern.len();
+        let value: String = source[start..].chars().take_while(char::is_ascii_digit).collect();
+        assert_eq!(value.parse::<usize>().unwrap(), FLAT_WORD_FIELDS);
+    }
+
+    #[test]
+    fn test_position_offset_round_trip() {
+        let counts = chapter_word_counts(&[3, 0, 2]).unwrap();
+        // Offset 3 crosses from chapter 0 over the empty chapter 1 into chapter 2
+        let positions: Vec<(u32, u32)> = (0..5).map(|offset| offset_to_position(&counts, offset)).collect();
+        assert_eq!(positions, vec![(0, 0), (0, 1), (0, 2), (2, 0), (2, 1)]);
+        for (offset, &(chapter, word)) in positions.iter().enumerate() {
+            assert_eq!(position_to_offset(&counts, chapter as usize, word), offset as u32);
+        }
+
+        assert_eq!(offset_to_position(&counts, 99), (2, 2));
+        assert_eq!(position_to_offset(&counts, 2, 2), 5);
+        assert_eq!(position_to_offset(&counts, 0, 99), 3);
+        assert_eq!(position_to_offset(&counts, 9, 0), 5);
+        assert_eq!(offset_to_position(&[], 4), (0, 0));
+    }
+
+    #[test]
+    fn test_chapter_word_counts_rejects_invalid() {
+        assert_eq!(chapter_word_counts(&[1, 2]), Some(vec![1, 2]));
+        assert_eq!(chapter_word_counts(&[1, -1]), None);
+        assert_eq!(chapter_word_counts(&[i32::MAX, i32::MAX, 2]), None);
+        assert_eq!(chapter_word_counts(&[i32::MAX, i32::MAX, 1]), Some(vec![i32::MAX as u32, i32::MAX as u32, 1]));
+    }
+}
@@ -0,0 +1,133 @@
+//! JSON form of a parsed `Book` (feature `json`), for serving books from a
+//! REST API.
+//!
+//! Field names are camelCase and match the Kotlin `NativeBook` classes that
+//! `jni.rs` builds, with the same integer codes:
+//!
+//! ```text
+//! {
+//!   "schemaVersion": "1",
+//!   "metadata": {
+//!     "title": string,
+//!     "authors": [string],          // primary author first
+//!     "series": string | null,
+//!     "seriesIndex": number | null,
+//!     "epubVersion": 2 | 3 | 0      // 0 if unknown
+//!   },
+//!   "chapters": [{
+//!     "index": number,              // spine index
+//!     "title": string,
+//!     "words": [{
+//!       "text": string,
+//!       "lengthBucket": 0-3,        // short, medium, long, veryLong
+//!       "followingPunct": 0-5       // none, comma, period, exclamation, question, paragraph
+//!     }],
+//!     "stats": ChapterStats
+//!   }],
+//!   "stats": {
+//!     "totalWords": number,
+//!     "aggregated": ChapterStats
+//!   }
+//! }
+//!
+//! ChapterStats = {
+//!   "wordCount": number,
+//!   "lengthCounts": [number; 4],    // indexed by lengthBucket
+//!   "punctCounts": [number; 6]      // indexed by followingPunct
+//! }
+//! ```
+
+use crate::types::{Book, BookMetadata, Chapter, ChapterStats, EpubVersion, Word};
+use serde_json::{json, Value};
+
+/// `schemaVersion` of the JSON produced here; bumped on incompatible changes.
+pub const SCHEMA_VERSION: &str = "1";
+
+impl From<&Book> for Value {
+    fn from(book: &Book) -> Self {
+        json!({
+            "schemaVersion": SCHEMA_VERSION,
+            "metadata": metadata_to_json(&book.metadata),
+            "chapters": book.chapters.iter().map(chapter_to_json).collect::<Vec<_>>(),
+            "stats": {
+                "totalWords": book.stats.total_words,
+                "aggregated": chapter_stats_to_json(&book.stats.aggregated),
+            },
+        })
+    }
+}
+
+impl From<Book> for Value {
+    fn from(book: Book) -> Self {
+        Value::from(&book)
+    }
+}
+
+fn metadata_to_json(metadata: &BookMetadata) -> Value {
+    let epub_version = match metadata.epub_version {
+        EpubVersion::Epub2 => 2,
+        EpubVersion::Epub3 => 3,
+        EpubVersion::Unknown(_) => 0,
+    };
+    json!({
+        "title": metadata.title,
+        "authors": metadata.authors().collect::<Vec<_>>(),
+        "series": metadata.series,
+        "seriesIndex": metadata.series_index,
+        "epubVersion": epub_version,
+    })
+}
+
+fn chapter_to_json(chapter: &Chapter) -> Value {
+    json!({
+        "index": chapter.index,
+        "title": chapter.title,
+        "words": chapter.words.iter().map(word_to_json).collect::<Vec<_>>(),
+        "stats": chapter_stats_to_json(&chapter.stats),
+    })
+}
+
+fn word_to_json(word: &Word) -> Value {
+    json!({
+        "text": word.text,
+        "lengthBucket": word.length_bucket as u8,
+        "followingPunct": word.following_punct as u8,
+    })
+}
+
+fn chapter_stats_to_json(stats: &ChapterStats) -> Value {
+    json!({
+        "wordCount": stats.word_count,
+        "lengthCounts": stats.length_counts,
+        "punctCounts": stats.punct_counts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_book_json_round_trip() {
+        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(epub_path).expect("Failed to read demo.epub");
+        let book = crate::epub::parse_epub(&data).unwrap();
+
+        let text = Value::from(&book).to_string();
+        let json: Value = serde_json::from_str(&text).unwrap();
+
+        assert_eq!(json["schemaVersion"], "1");
+        assert_eq!(json["metadata"]["title"], "Understanding Speed Reading");
+        assert_eq!(json["metadata"]["authors"].as_array().unwrap().len(), book.metadata.authors().count());
+        assert_eq!(json["chapters"].as_array().unwrap().len(), book.chapters.len());
+        assert_eq!(json["stats"]["totalWords"], book.stats.total_words);
+        assert_eq!(json["stats"]["aggregated"]["lengthCounts"].as_array().unwrap().len(), 4);
+
+        let chapter = &json["chapters"][0];
+        assert_eq!(chapter["title"], book.chapters[0].title.as_str());
+        assert_eq!(chapter["stats"]["wordCount"], book.chapters[0].stats.word_count);
+        let word = &chapter["words"][0];
+        assert_eq!(word["text"], book.chapters[0].words[0].text.as_str());
+        assert_eq!(word["lengthBucket"], book.chapters[0].words[0].length_bucket as u8);
+    }
+}
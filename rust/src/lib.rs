@@ -3,65 +3,71 @@
 //! This library provides fast, efficient EPUB parsing with pre-computed
 //! statistics for O(1) effective WPM calculation.
 
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod bookmarks;
 pub mod epub;
 pub mod jni;
+#[cfg(feature = "json")]
+pub mod json;
 pub mod tokenizer;
 pub mod types;
 
-pub use epub::{parse_epub, parse_epub_with_config};
-pub use types::{Book, BookMetadata, BookStats, Chapter, ChapterStats, Word};
+pub use bookmarks::{Bookmark, CfiError};
+pub use epub::{
+    export_epub, parse_chapters_from_html_bytes, parse_epub, parse_epub_from_path, parse_epub_from_path_with_config, parse_epub_from_reader,
+    parse_epub_from_reader_with_config, parse_epub_with_config, parse_epub_with_progress, read_epub_metadata,
+    ChapterFilter, EpubValidator, LazyBook, ParseConfig, RenditionSelector, Severity, ValidationIssue,
+};
+#[cfg(feature = "tokio")]
+pub use epub::{parse_epub_async, parse_epub_with_config_async};
+pub use tokenizer::{
+    create_chapter, create_chapter_with_config, tokenize_with_stats, ParagraphPauseMode, TokenizeConfig, Tokenizer, TokenizerStats,
+};
+pub use types::{
+    Book, BookDiff, BookMetadata, BookStats, Chapter, ChapterStats, ChunkRole, EpubGuide, EpubVersion, Footnote, Paragraph, ParseWarning,
+    ReadingPosition, SplitError, Word, WordRef,
+};
+
+/// The types and entry points most client code needs:
+/// `use spread_core::prelude::*;` brings in
+///
+/// - `parse_epub`, `parse_epub_with_config` and `ParseConfig`
+/// - `Book`, `BookMetadata`, `BookStats`, `Chapter`, `ChapterStats`, `Word`
+/// - `LengthBucket` and `Punctuation`, the per-word timing metadata
+///
+/// Everything else (tokenizer configuration, lazy parsing, bookmarks,
+/// validation) is imported from the crate root as needed. The JNI bindings
+/// are never exported here.
+pub mod prelude {
+    pub use crate::epub::{parse_epub, parse_epub_with_config, ParseConfig};
+    pub use crate::types::{Book, BookMetadata, BookStats, Chapter, ChapterStats, LengthBucket, Punctuation, Word};
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Instant;
 
     #[test]
-    fn test_tokenizer_integration() {
-        let words = tokenizer::tokenize("Hello, world! This is a test.");
-        assert_eq!(words.len(), 6);
+    fn test_prelude() {
+        use crate::prelude::*;
+
+        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/assets/demo.epub");
+        let data = std::fs::read(epub_path).expect("Failed to read demo.epub");
+        let config = ParseConfig {
+            max_chunk_chars: 12,
+            ..Default::default()
+        };
+        let book: Book = parse_epub_with_config(&data, &config).unwrap();
+        let first: &Word = &book.chapters[0].words[0];
+        assert!(first.length_bucket <= LengthBucket::VeryLong);
+        assert_eq!(book.chapters.len(), parse_epub(&data).unwrap().chapters.len());
     }
 
     #[test]
-    fn bench_parse_epub() {
-        // Load test EPUB from fixtures
-        let epub_path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pride-and-prejudice.epub");
-        let data = std::fs::read(epub_path)
-            .expect("Test fixture not found - run: cp pride-and-prejudice.epub rust/tests/fixtures/");
-
-        println!("\n=== EPUB Parsing Benchmark ===");
-        println!("File: {} ({:.2} KB)", epub_path, data.len() as f64 / 1024.0);
-
-        // Warm up
-        let _ = parse_epub(&data);
-
-        // Benchmark multiple runs
-        let runs = 5;
-        let mut times = Vec::new();
-
-        for i in 0..runs {
-            let start = Instant::now();
-            let book = parse_epub(&data).expect("Parse failed");
-            let elapsed = start.elapsed();
-            times.push(elapsed);
-
-            if i == 0 {
-                println!("\nBook: {}", book.metadata.title);
-                println!("Author: {}", book.metadata.author.as_deref().unwrap_or("Unknown"));
-                println!("Chapters: {}", book.chapters.len());
-                println!("Total words: {}", book.stats.total_words);
-            }
-        }
-
-        let avg_ms = times.iter().map(|t| t.as_secs_f64() * 1000.0).sum::<f64>() / runs as f64;
-        let min_ms = times.iter().map(|t| t.as_secs_f64() * 1000.0).fold(f64::INFINITY, f64::min);
-        let max_ms = times.iter().map(|t| t.as_secs_f64() * 1000.0).fold(0.0, f64::max);
-
-        println!("\nParsing times ({} runs):", runs);
-        println!("  Min: {:.2}ms", min_ms);
-        println!("  Max: {:.2}ms", max_ms);
-        println!("  Avg: {:.2}ms", avg_ms);
-        println!("==============================\n");
+    fn test_tokenizer_integration() {
+        let words = tokenizer::tokenize("Hello, world! This is a test.");
+        assert_eq!(words.len(), 6);
     }
 }
 
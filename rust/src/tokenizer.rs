@@ -1,38 +1,118 @@
 //! Text tokenization with pre-computed metadata.
 
-use crate::types::{ChapterStats, LengthBucket, Punctuation, Word};
+use crate::types::{is_wide_char, ChapterStats, ChunkRole, LengthBucket, Paragraph, Punctuation, Word};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 /// Default maximum alphanumeric characters per chunk.
 /// With hyphens (up to 2), max display is 12 chars - fits 320dp screens.
 pub const DEFAULT_MAX_CHUNK_CHARS: usize = 10;
 
-/// Minimum chunk size to avoid tiny fragments that slow comprehension.
-const MIN_CHUNK_CHARS: usize = 3;
+/// Default minimum chunk size to avoid tiny fragments that slow comprehension.
+/// A prefix or suffix is only split off if at least this many letters remain.
+pub const DEFAULT_MIN_CHUNK_CHARS: usize = 3;
+
+/// Default `TokenizeConfig::short_paragraph_words`.
+pub const DEFAULT_SHORT_PARAGRAPH_WORDS: usize = 4;
+
+/// How paragraph breaks are paced by `tokenize_paragraphs_with_config`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParagraphPauseMode {
+    /// Every break is a single `Punctuation::Paragraph` on the last word.
+    #[default]
+    Uniform,
+    /// Paragraphs shorter than `short_paragraph_words` get an extra pause word.
+    ProportionalToLength,
+    /// Like `ProportionalToLength`, but only for short paragraphs that don't end
+    /// a sentence (headings such as "Chapter One").
+    HeadingDetected,
+}
+
+/// Per-call tokenizer settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenizeConfig {
+    /// Max letters per chunk (default 10, range 10-22).
+    pub max_chunk_chars: usize,
+    /// Min letters left after splitting off a prefix or suffix (default 3).
+    pub min_chunk_chars: usize,
+    /// Words with fewer letters than this are never split (default max_chunk_chars + 1).
+    pub min_split_length: usize,
+    /// Set to false to keep every word whole.
+    pub split_enabled: bool,
+    /// Extra pacing after short paragraphs (default `Uniform`).
+    pub paragraph_pause_mode: ParagraphPauseMode,
+    /// Paragraphs with fewer words than this count as short (default 4).
+    pub short_paragraph_words: usize,
+}
+
+impl TokenizeConfig {
+    /// Default config with a different max chunk size; `min_split_length`
+    /// follows it so that only words longer than one chunk are split.
+    pub fn with_max_chunk_chars(max_chunk_chars: usize) -> Self {
+        TokenizeConfig {
+            max_chunk_chars,
+            min_split_length: max_chunk_chars + 1,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TokenizeConfig {
+    fn default() -> Self {
+        TokenizeConfig {
+            max_chunk_chars: DEFAULT_MAX_CHUNK_CHARS,
+            min_chunk_chars: DEFAULT_MIN_CHUNK_CHARS,
+            min_split_length: DEFAULT_MAX_CHUNK_CHARS + 1,
+            split_enabled: true,
+            paragraph_pause_mode: ParagraphPauseMode::Uniform,
+            short_paragraph_words: DEFAULT_SHORT_PARAGRAPH_WORDS,
+        }
+    }
+}
+
+/// Builds an affix table of `(affix, byte length)` pairs, so the split loop
+/// doesn't recompute lengths.
+macro_rules! affixes {
+    ($($affix:literal),* $(,)?) => {
+        &[$(($affix, $affix.len())),*]
+    };
+}
+
+/// Whether every entry's length matches its affix and entries are sorted by
+/// length descending. Checked at compile time for the built-in tables.
+const fn is_valid_affix_table(table: &[(&str, usize)]) -> bool {
+    let mut i = 0;
+    while i < table.len() {
+        if table[i].0.len() != table[i].1 || (i > 0 && table[i].1 > table[i - 1].1) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
 
 /// Common English prefixes for morphological splitting (~100 entries).
 /// Sorted by length descending so longer prefixes match first (e.g., "inter" before "in").
 /// Covers scientific, technical, and common vocabulary.
-const PREFIXES: &[&str] = &[
-    // 7+ chars
-    "counter", "electro", "pseudo",
-    // 6 chars
-    "circum", "contra", "extra", "hetero", "homeo", "infra", "macro", "micro",
-    "neuro", "pseudo", "psycho", "stereo", "thermo",
-    // 5 chars
-    "after", "anti", "auto", "cross", "cyber", "extra", "hyper", "inter",
-    "intra", "intro", "macro", "mega", "meta", "micro", "multi", "ortho",
-    "osteo", "paleo", "para", "penta", "photo", "poly", "post", "proto",
-    "quasi", "retro", "semi", "socio", "super", "supra", "tetra", "trans",
-    "ultra", "under", "video",
-    // 4 chars
-    "ante", "anti", "arch", "auto", "back", "down", "fore", "hemi", "homo",
-    "hypo", "kilo", "mega", "meta", "midi", "midi", "mini", "mono", "nano",
-    "omni", "over", "para", "peri", "poly", "post", "self", "step", "tele",
-    "tri", "vice", "with",
-    // 3 chars
-    "bio", "dis", "eco", "geo", "iso", "mid", "mis", "neo", "non", "out",
+const PREFIXES: &[(&str, usize)] = affixes![
+    // 7 bytes
+    "counter", "electro",
+    // 6 bytes
+    "pseudo", "circum", "contra", "hetero", "psycho", "stereo", "thermo",
+    // 5 bytes
+    "extra", "homeo", "infra", "macro", "micro", "neuro", "after", "cross",
+    "cyber", "hyper", "inter", "intra", "intro", "multi", "ortho", "osteo",
+    "paleo", "penta", "photo", "proto", "quasi", "retro", "socio", "super",
+    "supra", "tetra", "trans", "ultra", "under", "video",
+    // 4 bytes
+    "anti", "auto", "mega", "meta", "para", "poly", "post", "semi", "ante",
+    "arch", "back", "down", "fore", "hemi", "homo", "hypo", "kilo", "midi",
+    "mini", "mono", "nano", "omni", "over", "peri", "self", "step", "tele",
+    "vice", "with",
+    // 3 bytes
+    "tri", "bio", "dis", "eco", "geo", "iso", "mid", "mis", "neo", "non", "out",
     "pan", "pre", "pro", "sub", "sur",
-    // 2 chars
+    // 2 bytes
     "ab", "ad", "be", "bi", "by", "co", "de", "di", "em", "en", "ex", "il",
     "im", "in", "ir", "ob", "re", "un", "up",
 ];
@@ -40,143 +120,291 @@ const PREFIXES: &[&str] = &[
 /// Common English suffixes for morphological splitting (~100 entries).
 /// Sorted by length descending so longer suffixes match first.
 /// Covers scientific, technical, and common vocabulary.
-const SUFFIXES: &[&str] = &[
-    // 8+ chars
-    "ological", "isation", "ization",
-    // 7 chars
-    "ability", "ibility", "aceous", "escent", "fulness", "isation", "ization",
-    "logical", "ousness",
-    // 6 chars
-    "arily", "ation", "ative", "atory", "eling", "ement", "ening", "ential",
-    "fully", "ially", "ible", "iness", "ional", "ising", "istic", "ition",
-    "itive", "ivity", "izing", "ology", "ously", "wards",
-    // 5 chars
-    "able", "ably", "ally", "ance", "ancy", "ator", "dom", "ence", "ency",
-    "eous", "erly", "hood", "ible", "ibly", "ical", "ious", "ised", "ised",
-    "ish", "ism", "ist", "ite", "itis", "ity", "ized", "less", "like",
-    "ling", "ment", "most", "ness", "ship", "sion", "teen", "tion", "tude",
-    "ular", "ward", "ways", "wise", "work",
-    // 4 chars
-    "able", "ance", "ancy", "arch", "cide", "cracy", "crat", "dom", "ence",
-    "ency", "ette", "fold", "form", "free", "gram", "hood", "ible", "ical",
-    "iest", "ious", "less", "like", "ling", "ment", "most", "ness", "onym",
-    "osis", "path", "phon", "port", "ship", "some", "ster", "teen", "ular",
-    "ward", "ware", "ways", "wise", "work",
-    // 3 chars
-    "age", "ain", "ary", "ate", "dom", "eer", "ent", "ery", "ese", "est",
-    "eur", "ful", "ial", "ian", "ics", "ier", "ify", "ile", "ine", "ing",
-    "ion", "ise", "ism", "ist", "ite", "ity", "ive", "ize", "let", "ock",
-    "oid", "ory", "ose", "ous", "ure",
-    // 2 chars
+const SUFFIXES: &[(&str, usize)] = affixes![
+    // 8 bytes
+    "ological",
+    // 7 bytes
+    "isation", "ization", "ability", "ibility", "fulness", "logical", "ousness",
+    // 6 bytes
+    "aceous", "escent", "ential",
+    // 5 bytes
+    "arily", "ation", "ative", "atory", "eling", "ement", "ening", "fully",
+    "ially", "iness", "ional", "ising", "istic", "ition", "itive", "ivity",
+    "izing", "ology", "ously", "wards", "cracy",
+    // 4 bytes
+    "ible", "able", "ably", "ally", "ance", "ancy", "ator", "ence", "ency",
+    "eous", "erly", "hood", "ibly", "ical", "ious", "ised", "itis", "ized",
+    "less", "like", "ling", "ment", "most", "ness", "ship", "sion", "teen",
+    "tion", "tude", "ular", "ward", "ways", "wise", "work", "arch", "cide",
+    "crat", "ette", "fold", "form", "free", "gram", "iest", "onym", "osis",
+    "path", "phon", "port", "some", "ster", "ware",
+    // 3 bytes
+    "dom", "ish", "ism", "ist", "ite", "ity", "age", "ain", "ary", "ate", "eer",
+    "ent", "ery", "ese", "est", "eur", "ful", "ial", "ian", "ics", "ier", "ify",
+    "ile", "ine", "ing", "ion", "ise", "ive", "ize", "let", "ock", "oid", "ory",
+    "ose", "ous", "ure",
+    // 2 bytes
     "al", "an", "ar", "ed", "en", "er", "ic", "id", "ie", "ly", "or", "ry",
     "th", "ty",
 ];
 
-/// Split a long word into chunks at morphological boundaries.
-/// Returns chunks with hyphens: ["Inter-", "national-", "-ization"]
-///
-/// `max_chunk_chars` controls max letters per chunk (default 10, range 10-22).
-/// Words are only split if they exceed max_chunk_chars.
-fn split_long_word(word: &str, max_chunk_chars: usize) -> Vec<String> {
-    let clean: String = word.chars().filter(|c| c.is_alphabetic()).collect();
+/// Common German prefixes (separable and inseparable verb prefixes).
+/// Sorted by length descending so longer prefixes match first.
+const GERMAN_PREFIXES: &[(&str, usize)] = affixes![
+    // 8 bytes
+    "zusammen", "entgegen",
+    // 6 bytes
+    "hinter", "wieder",
+    // 5 bytes
+    "gegen", "unter", "wider", "durch", "über",
+    // 4 bytes
+    "nach", "voll", "miss", "fort",
+    // 3 bytes
+    "weg", "ent", "emp", "ver", "zer", "vor", "aus", "auf", "ein", "mit", "bei",
+    // 2 bytes
+    "ab", "an", "be", "er", "ge", "um", "zu", "un",
+];
 
-    // Only split if word exceeds the max (with 2 char buffer for hyphens in display)
-    let min_split_length = max_chunk_chars + 1;
-    let clean_lower = clean.to_lowercase();
+/// Common German derivational and inflectional suffixes.
+/// Sorted by length descending so longer suffixes match first.
+const GERMAN_SUFFIXES: &[(&str, usize)] = affixes![
+    // 6 bytes
+    "schaft",
+    // 5 bytes
+    "ungen", "ismus",
+    // 4 bytes
+    "keit", "heit", "lich", "isch", "chen", "lein", "haft",
+    // 3 bytes
+    "sam", "bar", "los", "tum", "nis", "ung", "ern", "end",
+    // 2 bytes
+    "en", "er", "es", "st",
+];
 
-    // Only split words that are long enough to benefit from splitting
-    if clean.len() < min_split_length {
-        return vec![word.to_string()];
-    }
+const _: () = assert!(
+    is_valid_affix_table(PREFIXES)
+        && is_valid_affix_table(SUFFIXES)
+        && is_valid_affix_table(GERMAN_PREFIXES)
+        && is_valid_affix_table(GERMAN_SUFFIXES),
+    "affix tables must be sorted by length descending"
+);
 
-    let mut chunks = Vec::new();
-    let mut remaining = clean.as_str();
-    let mut remaining_lower = clean_lower.as_str();
-    let mut is_first = true;
+/// Affix tables and split threshold for one language.
+///
+/// The free functions in this module (`tokenize`, `tokenize_with_config`, ...)
+/// use `Tokenizer::english()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tokenizer {
+    /// `(prefix, byte length)` pairs tried in order; list longer ones first.
+    pub prefixes: &'static [(&'static str, usize)],
+    /// `(suffix, byte length)` pairs tried in order; list longer ones first.
+    pub suffixes: &'static [(&'static str, usize)],
+    /// Words with fewer letters than this are never split by `tokenize`.
+    pub min_split_length: usize,
+}
 
-    // Try to extract prefix
-    let mut prefix_len = 0;
-    for prefix in PREFIXES {
-        if remaining_lower.starts_with(prefix) && remaining.len() > prefix.len() + MIN_CHUNK_CHARS {
-            prefix_len = prefix.len();
-            break;
+impl Tokenizer {
+    /// English affix tables.
+    pub const fn english() -> Self {
+        Tokenizer {
+            prefixes: PREFIXES,
+            suffixes: SUFFIXES,
+            min_split_length: DEFAULT_MAX_CHUNK_CHARS + 1,
         }
     }
 
-    if prefix_len > 0 {
-        chunks.push(format!("{}-", &remaining[..prefix_len]));
-        remaining = &remaining[prefix_len..];
-        remaining_lower = &remaining_lower[prefix_len..];
-        is_first = false;
+    /// German affix tables. Compounds are split at fixed-width chunks between
+    /// the affixes, since compound boundaries are not detected.
+    pub const fn german() -> Self {
+        Tokenizer {
+            prefixes: GERMAN_PREFIXES,
+            suffixes: GERMAN_SUFFIXES,
+            min_split_length: DEFAULT_MAX_CHUNK_CHARS + 1,
+        }
     }
 
-    // Try to extract suffix from the end
-    let mut suffix_len = 0;
-    let mut suffix_text = String::new();
-    for suffix in SUFFIXES {
-        if remaining_lower.ends_with(suffix) && remaining.len() > suffix.len() + MIN_CHUNK_CHARS {
-            suffix_len = suffix.len();
-            suffix_text = format!("-{}", &remaining[remaining.len() - suffix_len..]);
-            break;
-        }
+    /// Tokenize with the default config and this tokenizer's `min_split_length`.
+    #[must_use]
+    pub fn tokenize(&self, text: &str) -> Vec<Word> {
+        let config = TokenizeConfig {
+            min_split_length: self.min_split_length,
+            ..Default::default()
+        };
+        self.tokenize_with_config(text, &config)
     }
 
-    // Get the middle part (excluding suffix if found)
-    let middle = if suffix_len > 0 {
-        &remaining[..remaining.len() - suffix_len]
-    } else {
-        remaining
-    };
+    /// Tokenize using this tokenizer's affix tables. All thresholds,
+    /// including `min_split_length`, come from `config`.
+    #[must_use]
+    pub fn tokenize_with_config(&self, text: &str, config: &TokenizeConfig) -> Vec<Word> {
+        tokenize_with_affixes(self, text, config, &mut TokenizerStats::default())
+    }
+
+    /// Split a long word into chunks at morphological boundaries.
+    /// Returns chunks with hyphens: ["Inter-", "national-", "-ization"]
+    ///
+    /// `config.max_chunk_chars` controls max letters per chunk (default 10, range 10-22).
+    /// Words are only split if they have at least `config.min_split_length` letters.
+    /// `config.min_chunk_chars` is the fewest letters that must remain after
+    /// removing a prefix or suffix for it to be split off (default 3).
+    ///
+    /// CJK words are never split: they have no hyphenation boundaries and are
+    /// already bucketed by display width.
+    fn split_long_word(&self, word: &str, config: &TokenizeConfig) -> Vec<String> {
+        if !config.split_enabled || word.chars().any(is_wide_char) {
+            return vec![word.to_string()];
+        }
+
+        let max_chunk_chars = config.max_chunk_chars;
+        let min_chunk_chars = config.min_chunk_chars;
+        // Keep apostrophes so possessives and contractions survive the split
+        // ("children's" stays 10 chars, and its chunks rejoin to the word)
+        let clean: String = word.chars().filter(|c| c.is_alphabetic() || *c == '\'').collect();
+        // Affixes are matched on the lowercase form but cut from `clean`, so
+        // both must have the same byte offsets
+        let clean_lower = lowercase_same_len(&clean);
+
+        // Only split words that are long enough to benefit from splitting
+        if clean.len() < config.min_split_length {
+            return vec![word.to_string()];
+        }
+
+        let mut chunks = Vec::new();
+        let mut remaining = clean.as_str();
+        let mut remaining_lower = clean_lower.as_str();
+        let mut is_first = true;
+
+        // Try to extract prefix
+        let mut prefix_len = 0;
+        for &(prefix, len) in self.prefixes {
+            if remaining_lower.starts_with(prefix)
+                && remaining.len() > len + min_chunk_chars
+                && remaining.is_char_boundary(len)
+            {
+                prefix_len = len;
+                break;
+            }
+        }
 
-    // Split middle into chunks of max_chunk_chars
-    if !middle.is_empty() {
-        let mut pos = 0;
-        while pos < middle.len() {
-            let end = (pos + max_chunk_chars).min(middle.len());
-            let chunk = &middle[pos..end];
+        if prefix_len > 0 {
+            chunks.push(format!("{}-", &remaining[..prefix_len]));
+            remaining = &remaining[prefix_len..];
+            remaining_lower = &remaining_lower[prefix_len..];
+            is_first = false;
+        }
 
-            let formatted = if is_first && pos + max_chunk_chars >= middle.len() && suffix_len == 0 {
+        // Try to extract suffix from the end
+        let mut suffix_len = 0;
+        let mut suffix_text = String::new();
+        for &(suffix, len) in self.suffixes {
+            if remaining_lower.ends_with(suffix)
+                && remaining.len() > len + min_chunk_chars
+                && remaining.is_char_boundary(remaining.len() - len)
+            {
+                suffix_len = len;
+                suffix_text = format!("-{}", &remaining[remaining.len() - suffix_len..]);
+                break;
+            }
+        }
+
+        // Get the middle part (excluding suffix if found)
+        let middle = if suffix_len > 0 {
+            &remaining[..remaining.len() - suffix_len]
+        } else {
+            remaining
+        };
+
+        // Split middle into chunks of max_chunk_chars (counted in chars, so
+        // non-ASCII letters are never cut in half)
+        let mut rest = middle;
+        while !rest.is_empty() {
+            let end = rest.char_indices().nth(max_chunk_chars).map_or(rest.len(), |(i, _)| i);
+            let (chunk, tail) = rest.split_at(end);
+            let is_last = tail.is_empty();
+
+            let formatted = if is_first && is_last && suffix_len == 0 {
                 // Only chunk, no suffix - don't add hyphens
                 chunk.to_string()
             } else if is_first {
                 format!("{}-", chunk)
-            } else if pos + max_chunk_chars >= middle.len() && suffix_len == 0 {
+            } else if is_last && suffix_len == 0 {
                 format!("-{}", chunk)
             } else {
                 format!("-{}-", chunk)
             };
 
             chunks.push(formatted);
-            pos = end;
+            rest = tail;
             is_first = false;
         }
-    }
 
-    // Add suffix if found
-    if suffix_len > 0 {
-        chunks.push(suffix_text);
-    }
+        // Add suffix if found
+        if suffix_len > 0 {
+            chunks.push(suffix_text);
+        }
 
-    // If we ended up with only one chunk, return original
-    if chunks.len() <= 1 {
-        return vec![word.to_string()];
-    }
+        // If we ended up with only one chunk, return original
+        if chunks.len() <= 1 {
+            return vec![word.to_string()];
+        }
 
-    chunks
+        chunks
+    }
 }
 
 /// Tokenize text into words with length buckets and punctuation info.
 /// Long words are split into multiple chunks for RSVP display.
 ///
-/// `max_chunk_chars` controls max letters per chunk (default 10, range 10-22).
-pub fn tokenize_with_config(text: &str, max_chunk_chars: usize) -> Vec<Word> {
-    let mut words = Vec::new();
+/// Words containing CJK characters are bucketed by display width
+/// (`LengthBucket::from_display_chars`); with the `cjk` feature enabled
+/// every word is.
+///
+/// See `TokenizeConfig` for the tunable splitting thresholds.
+#[must_use]
+pub fn tokenize_with_config(text: &str, config: &TokenizeConfig) -> Vec<Word> {
+    Tokenizer::english().tokenize_with_config(text, config)
+}
+
+/// Counts gathered by `tokenize_with_stats`, for debugging how the
+/// tokenizer handles a particular text.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TokenizerStats {
+    /// Words produced, counting each chunk of a split word
+    pub total_tokens: u32,
+    /// Source words that were split into chunks
+    pub split_words: u32,
+    /// Characters in all produced words, including chunk hyphens
+    pub total_chars: u32,
+    /// `total_chars / total_tokens`, 0.0 for no tokens
+    pub avg_token_length: f32,
+}
 
-    for raw in text.split_whitespace() {
+/// Tokenize with the default config, counting tokens, split words and
+/// characters along the way.
+#[must_use]
+pub fn tokenize_with_stats(text: &str) -> (Vec<Word>, TokenizerStats) {
+    let mut stats = TokenizerStats::default();
+    let words = tokenize_with_affixes(&Tokenizer::english(), text, &TokenizeConfig::default(), &mut stats);
+    (words, stats)
+}
+
+fn tokenize_with_affixes(tokenizer: &Tokenizer, text: &str, config: &TokenizeConfig, stats: &mut TokenizerStats) -> Vec<Word> {
+    let mut words: Vec<Word> = Vec::new();
+
+    for raw in pad_dashes(text).split_whitespace() {
         if raw.is_empty() {
             continue;
         }
 
+        // A dash between words is a comma-length pause, not a word
+        if raw == "—" {
+            if let Some(previous) = words.last_mut() {
+                if previous.following_punct == Punctuation::None {
+                    previous.following_punct = Punctuation::Comma;
+                }
+            }
+            continue;
+        }
+
         // Count only alphanumeric chars for length bucket
         let clean_len = raw
             .chars()
@@ -194,15 +422,38 @@ pub fn tokenize_with_config(text: &str, max_chunk_chars: usize) -> Vec<Word> {
             .map(Punctuation::from_char)
             .unwrap_or(Punctuation::None);
 
+        // Links are shown whole: splitting "https://example.com/path" at
+        // affixes would only produce fragments of the address
+        if is_url_or_email(raw) {
+            stats.total_chars += raw.chars().count() as u32;
+            words.push(Word {
+                text: raw.to_string(),
+                length_bucket: LengthBucket::VeryLong,
+                following_punct,
+                is_chunk: false,
+                chunk_role: ChunkRole::Whole,
+                is_url: true,
+            });
+            continue;
+        }
+
         // Split long words
-        let chunks = split_long_word(raw, max_chunk_chars);
+        let chunks = tokenizer.split_long_word(raw, config);
         let chunk_count = chunks.len();
+        if chunk_count > 1 {
+            stats.split_words += 1;
+        }
 
         for (i, chunk) in chunks.into_iter().enumerate() {
-            let chunk_clean_len = chunk
-                .chars()
-                .filter(|c| c.is_alphanumeric() || *c == '\'' || *c == '-')
-                .count();
+            let length_bucket = if cfg!(feature = "cjk") || chunk.chars().any(is_wide_char) {
+                LengthBucket::from_display_chars(&chunk)
+            } else {
+                let chunk_clean_len = chunk
+                    .chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '\'' || *c == '-')
+                    .count();
+                LengthBucket::from_length(chunk_clean_len)
+            };
 
             // Only last chunk gets the original punctuation
             let punct = if i == chunk_count - 1 {
@@ -211,75 +462,628 @@ pub fn tokenize_with_config(text: &str, max_chunk_chars: usize) -> Vec<Word> {
                 Punctuation::None
             };
 
+            let chunk_role = match i {
+                _ if chunk_count == 1 => ChunkRole::Whole,
+                0 => ChunkRole::First,
+                _ if i == chunk_count - 1 => ChunkRole::Last,
+                _ => ChunkRole::Middle,
+            };
+
+            stats.total_chars += chunk.chars().count() as u32;
             words.push(Word {
                 text: chunk,
-                length_bucket: LengthBucket::from_length(chunk_clean_len),
+                length_bucket,
                 following_punct: punct,
+                is_chunk: chunk_count > 1,
+                chunk_role,
+                is_url: false,
             });
         }
     }
 
+    stats.total_tokens = words.len() as u32;
+    if stats.total_tokens > 0 {
+        stats.avg_token_length = stats.total_chars as f32 / stats.total_tokens as f32;
+    }
     words
 }
 
-/// Tokenize with default chunk size (10 chars).
+/// Lowercase `text` char by char, keeping any char whose lowercase form
+/// has a different UTF-8 length ('ẞ', the Kelvin sign 'K', ...) as is, so
+/// byte offsets into the result are valid in `text`.
+fn lowercase_same_len(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+/// True for a token that looks like a URL (`http://`, `https://` or
+/// `www.`) or an email address (`name@host.tld`), ignoring surrounding
+/// quotes and brackets.
+fn is_url_or_email(raw: &str) -> bool {
+    let token = raw.trim_matches(|c: char| matches!(c, '"' | '\'' | '(' | ')' | '<' | '>' | '[' | ']' | '“' | '”' | '‘' | '’'));
+    let lower = token.to_ascii_lowercase();
+    if ["http://", "https://", "www."].iter().any(|scheme| lower.starts_with(scheme) && lower.len() > scheme.len()) {
+        return true;
+    }
+    match token.split_once('@') {
+        Some((local, domain)) => {
+            let domain = domain.trim_end_matches(|c: char| !c.is_alphanumeric());
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.split('.').count() > 1
+                && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    }
+}
+
+/// Surround em dashes, en dashes and `--` with spaces, as `" — "`, so
+/// "word—word" splits into two words around a standalone dash token.
+fn pad_dashes(text: &str) -> Cow<'_, str> {
+    if !text.contains(['—', '–']) && !text.contains("--") {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace("--", " — ").replace(['—', '–'], " — "))
+}
+
+/// Tokenize with the default config (10 char chunks).
+#[must_use]
 pub fn tokenize(text: &str) -> Vec<Word> {
-    tokenize_with_config(text, DEFAULT_MAX_CHUNK_CHARS)
+    tokenize_with_config(text, &TokenizeConfig::default())
 }
 
 /// Tokenize multiple paragraphs, marking paragraph breaks.
-pub fn tokenize_paragraphs_with_config(paragraphs: &[&str], max_chunk_chars: usize) -> Vec<Word> {
-    let mut all_words = Vec::new();
-    let para_count = paragraphs.len();
+///
+/// With a non-`Uniform` `paragraph_pause_mode`, short paragraphs are followed
+/// by a synthetic pause word (see `Word::is_pause`).
+#[must_use]
+pub fn tokenize_paragraphs_with_config(paragraphs: &[&str], config: &TokenizeConfig) -> Vec<Word> {
+    tokenize_paragraphs_iter_with_config(paragraphs.iter().copied(), config)
+}
 
-    for (p_idx, para) in paragraphs.iter().enumerate() {
-        let words = tokenize_with_config(para, max_chunk_chars);
-        if words.is_empty() {
-            continue;
-        }
+/// `tokenize_paragraphs_with_config` over a stream of paragraphs, so the
+/// caller doesn't have to collect them into a slice first.
+#[must_use]
+pub fn tokenize_paragraphs_iter_with_config<'a>(
+    paragraphs: impl IntoIterator<Item = &'a str>,
+    config: &TokenizeConfig,
+) -> Vec<Word> {
+    let mut words = Vec::new();
+    for_each_paragraph_group(paragraphs, config, |group| words.extend(group));
+    words
+}
 
-        // Add all words except last
-        if words.len() > 1 {
-            all_words.extend_from_slice(&words[..words.len() - 1]);
-        }
+/// Tokenize paragraphs one at a time, passing each non-empty one's words to
+/// `f`. Reads one paragraph ahead to know whether the current one is last.
+fn for_each_paragraph_group<'a>(
+    paragraphs: impl IntoIterator<Item = &'a str>,
+    config: &TokenizeConfig,
+    mut f: impl FnMut(Vec<Word>),
+) {
+    let mut paragraphs = paragraphs.into_iter().peekable();
+
+    while let Some(para) = paragraphs.next() {
+        let mut words = tokenize_with_config(para, config);
+        let para_words = words.len();
+        let Some(last_word) = words.last_mut() else {
+            continue;
+        };
+
+        let is_break = paragraphs.peek().is_some();
+        let needs_pause = is_break
+            && para_words < config.short_paragraph_words
+            && match config.paragraph_pause_mode {
+                ParagraphPauseMode::Uniform => false,
+                ParagraphPauseMode::ProportionalToLength => true,
+                ParagraphPauseMode::HeadingDetected => matches!(
+                    last_word.following_punct,
+                    Punctuation::None | Punctuation::Comma
+                ),
+            };
 
         // Mark last word with paragraph punctuation if not already marked
-        let mut last_word = words.last().unwrap().clone();
-        if p_idx < para_count - 1 && last_word.following_punct == Punctuation::None {
+        if is_break && last_word.following_punct == Punctuation::None {
             last_word.following_punct = Punctuation::Paragraph;
         }
-        all_words.push(last_word);
+        if needs_pause {
+            words.push(Word::pause());
+        }
+        f(words);
     }
-
-    all_words
 }
 
 /// Tokenize paragraphs with default chunk size.
+#[must_use]
 pub fn tokenize_paragraphs(paragraphs: &[&str]) -> Vec<Word> {
-    tokenize_paragraphs_with_config(paragraphs, DEFAULT_MAX_CHUNK_CHARS)
+    tokenize_paragraphs_with_config(paragraphs, &TokenizeConfig::default())
+}
+
+/// `tokenize_paragraphs`, also returning the index in the words of each
+/// paragraph's first word, for "jump to paragraph N". Paragraphs without
+/// words get no entry.
+#[must_use]
+pub fn tokenize_paragraphs_indexed(paragraphs: &[&str]) -> (Vec<Word>, Vec<u32>) {
+    tokenize_paragraphs_indexed_with_config(paragraphs, &TokenizeConfig::default())
+}
+
+/// `tokenize_paragraphs_indexed` with a tokenizer config.
+#[must_use]
+pub fn tokenize_paragraphs_indexed_with_config(paragraphs: &[&str], config: &TokenizeConfig) -> (Vec<Word>, Vec<u32>) {
+    let mut words = Vec::new();
+    let mut offsets = Vec::new();
+    for_each_paragraph_group(paragraphs.iter().copied(), config, |group| {
+        offsets.push(words.len() as u32);
+        words.extend(group);
+    });
+    (words, offsets)
+}
+
+/// Streaming `tokenize_paragraphs`; see `tokenize_paragraphs_iter_with_config`.
+#[must_use]
+pub fn tokenize_paragraphs_iter<'a>(paragraphs: impl IntoIterator<Item = &'a str>) -> Vec<Word> {
+    tokenize_paragraphs_iter_with_config(paragraphs, &TokenizeConfig::default())
+}
+
+/// Number of distinct words (case-insensitive, surrounding punctuation ignored).
+/// Split-word chunks are rejoined so "inter-" "-national" counts once as "international".
+pub fn unique_word_count(words: &[Word]) -> u32 {
+    let mut vocabulary = HashSet::new();
+    collect_vocabulary(words, &mut vocabulary);
+    vocabulary.len() as u32
+}
+
+/// Group words into sentences, each ending at a word followed by a
+/// sentence mark (`.`, `!`, `?`) or a paragraph break. Trailing words with
+/// no closing mark form a last sentence; pause words are left out.
+pub fn split_into_sentences(words: &[Word]) -> Vec<Vec<Word>> {
+    sentences(words)
+        .map(|sentence| sentence.iter().filter(|w| !w.is_pause()).cloned().collect())
+        .collect()
 }
 
-/// Create chapter from title and paragraphs with configurable chunk size.
+/// Words per sentence, as `split_into_sentences` would group them, without
+/// cloning the words.
+pub fn sentence_word_counts(words: &[Word]) -> Vec<u32> {
+    sentences(words)
+        .map(|sentence| sentence.iter().filter(|w| !w.is_pause()).count() as u32)
+        .collect()
+}
+
+/// Sentence slices of `words`, skipping those made only of pause words.
+fn sentences(words: &[Word]) -> impl Iterator<Item = &[Word]> {
+    words
+        .split_inclusive(|w| {
+            matches!(
+                w.following_punct,
+                Punctuation::Period | Punctuation::Exclamation | Punctuation::Question | Punctuation::Paragraph
+            )
+        })
+        .filter(|sentence| sentence.iter().any(|w| !w.is_pause()))
+}
+
+/// Add the normalized words to `vocabulary`, returning how many words were seen
+/// (split chunks counting as one). Words that are already lowercase are
+/// borrowed rather than copied.
+pub(crate) fn collect_vocabulary<'a>(words: &'a [Word], vocabulary: &mut HashSet<Cow<'a, str>>) -> u32 {
+    let mut total = 0;
+    for_each_vocabulary_word(words, |word| {
+        vocabulary.insert(word);
+        total += 1;
+    });
+    total
+}
+
+/// Add each word's occurrences to `frequencies`, normalized as in `collect_vocabulary`.
+pub(crate) fn count_word_frequencies(words: &[Word], frequencies: &mut HashMap<String, u32>) {
+    for_each_vocabulary_word(words, |word| {
+        *frequencies.entry(word.into_owned()).or_insert(0) += 1;
+    });
+}
+
+/// Call `f` with every normalized word, rejoining split chunks and skipping
+/// tokens with no letters or digits.
+fn for_each_vocabulary_word<'a>(words: &'a [Word], mut f: impl FnMut(Cow<'a, str>)) {
+    let mut pending_chunks = String::new();
+
+    for word in words {
+        if word.is_chunk {
+            pending_chunks.push_str(word.text.trim_matches('-'));
+            if word.chunk_role != ChunkRole::Last {
+                continue;
+            }
+        }
+
+        let text: Cow<'a, str> = if pending_chunks.is_empty() {
+            Cow::Borrowed(&word.text)
+        } else {
+            Cow::Owned(std::mem::take(&mut pending_chunks))
+        };
+        let normalized = match text {
+            Cow::Borrowed(t) => normalize_vocabulary_word(t),
+            Cow::Owned(t) => Cow::Owned(normalize_vocabulary_word(&t).into_owned()),
+        };
+        if !normalized.is_empty() {
+            f(normalized);
+        }
+    }
+}
+
+fn normalize_vocabulary_word(text: &str) -> Cow<'_, str> {
+    let trimmed = text.trim_matches(|c: char| !c.is_alphanumeric());
+    if trimmed.chars().any(char::is_uppercase) {
+        Cow::Owned(trimmed.to_lowercase())
+    } else {
+        Cow::Borrowed(trimmed)
+    }
+}
+
+/// Estimate the number of English syllables in a word.
+///
+/// Counts groups of consecutive vowels (a, e, i, o, u and y), then:
+/// - drops a silent trailing 'e' ("make"), but keeps a consonant + "le"
+///   ending ("table"),
+/// - splits "ia", "io" and "iu" into two syllables ("media", "radio") except
+///   after c, g, s, t or x, where the i is usually silent ("special",
+///   "region", "Asia", "nation"),
+/// - counts a final "m" after a consonant cluster ending in "th" or "s"
+///   as its own syllable ("rhythm", "prism").
+///
+/// This is a heuristic: it's right for most common words but misses
+/// hiatus in other vowel pairs ("create"), silent "-ed" ("jumped") and the
+/// like, and knows nothing about other languages. Non-letters are ignored
+/// and the result is always at least 1.
+pub fn count_syllables(word: &str) -> u32 {
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count: u32 = 0;
+    let mut prev_vowel = false;
+    for (i, &c) in letters.iter().enumerate() {
+        let vowel = is_vowel(c);
+        if vowel && !prev_vowel {
+            count += 1;
+            let before = i.checked_sub(1).map(|j| letters[j]);
+            let hiatus = c == 'i'
+                && matches!(letters.get(i + 1), Some('a' | 'o' | 'u'))
+                && !matches!(before, Some('c' | 'g' | 's' | 't' | 'x'));
+            if hiatus {
+                count += 1;
+            }
+        }
+        prev_vowel = vowel;
+    }
+
+    if letters.len() > 2 && letters.ends_with(&['e']) && !letters.ends_with(&['l', 'e']) {
+        count = count.saturating_sub(1);
+    }
+    if letters.ends_with(&['t', 'h', 'm']) || (letters.len() > 3 && letters.ends_with(&['s', 'm'])) {
+        count += 1;
+    }
+
+    count.max(1)
+}
+
+/// Tokenize raw HTML that may not be well-formed XML.
+///
+/// Unlike the XHTML path in `epub.rs`, this never fails: unclosed `<p>`,
+/// `<br>` and `<img>` tags, stray end tags and unknown entities are all
+/// tolerated. Block-level tags become paragraph breaks.
+pub fn tokenize_html(html: &[u8]) -> Vec<Word> {
+    let text = extract_text_from_html(html);
+    let paragraphs: Vec<&str> = text
+        .split("\n\n")
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    tokenize_paragraphs(&paragraphs)
+}
+
+/// Tags whose content is never displayed.
+const HTML_SKIP_TAGS: &[&str] = &["script", "style", "head", "title", "rt"];
+
+/// Tags that start a new paragraph.
+const HTML_BLOCK_TAGS: &[&str] = &[
+    "p", "div", "br", "h1", "h2", "h3", "h4", "h5", "h6", "li", "blockquote", "section", "tr",
+];
+
+/// True for an `epub:type` token marking a footnote or endnote.
+pub(crate) fn is_note_type(token: &str) -> bool {
+    token == "footnote" || token == "endnote"
+}
+
+/// True for a start tag marked as a footnote or endnote by a prefixed
+/// `type` attribute. Without namespace declarations to go by, any prefix
+/// is accepted (`epub:type`, `ops:type`, ...).
+fn is_html_note_tag(tag: &str) -> bool {
+    const TYPE_ATTR: &str = ":type";
+    let mut rest = tag;
+    while let Some(i) = rest.find(TYPE_ATTR) {
+        rest = &rest[i + TYPE_ATTR.len()..];
+        let Some(value) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+            _ => value.split(|c: char| c.is_whitespace() || c == '>').next().unwrap_or(""),
+        };
+        if value.split_whitespace().any(is_note_type) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Lenient HTML to text conversion: paragraphs separated by blank lines.
+/// Footnotes and endnotes are left out, as in the XHTML path.
+pub(crate) fn extract_text_from_html(html: &[u8]) -> String {
+    let html = String::from_utf8_lossy(html);
+    let lower = html.to_ascii_lowercase();
+
+    // Restrict to <body> when present
+    let start = lower
+        .find("<body")
+        .and_then(|i| lower[i..].find('>').map(|j| i + j + 1))
+        .unwrap_or(0);
+    let end = lower[start..].find("</body").map(|i| start + i).unwrap_or(html.len());
+
+    let mut result = String::new();
+    let mut pos = start;
+    // Tag name of the open footnote and how deeply it is nested in itself
+    let mut note: Option<(String, usize)> = None;
+
+    while pos < end {
+        let rest = &html[pos..end];
+        let lower_rest = &lower[pos..end];
+
+        if lower_rest.starts_with("<!--") {
+            pos += lower_rest.find("-->").map(|i| i + 3).unwrap_or(rest.len());
+        } else if lower_rest.starts_with("<![cdata[") {
+            let body_end = rest.find("]]>").unwrap_or(rest.len());
+            if note.is_none() {
+                push_html_text(&mut result, &rest["<![CDATA[".len().min(body_end)..body_end]);
+            }
+            pos += (body_end + 3).min(rest.len());
+        } else if rest.starts_with('<') {
+            let tag_end = find_tag_end(rest);
+            let (name, closing) = html_tag_name(&lower_rest[..tag_end]);
+            let self_closing = rest[..tag_end].ends_with("/>");
+            pos += tag_end;
+
+            if let Some((note_name, depth)) = note.as_mut() {
+                if *note_name == name && !self_closing {
+                    if !closing {
+                        *depth += 1;
+                    } else if *depth > 1 {
+                        *depth -= 1;
+                    } else {
+                        note = None;
+                    }
+                }
+            } else if name.is_empty() {
+                // Not a tag (e.g. "a < b"), keep the literal text
+                push_html_text(&mut result, &rest[..tag_end]);
+            } else if !closing && !self_closing && is_html_note_tag(&lower_rest[..tag_end]) {
+                note = Some((name, 1));
+            } else if !closing && !self_closing && HTML_SKIP_TAGS.contains(&name.as_str()) {
+                let close = format!("</{}", name);
+                pos = lower[pos..end]
+                    .find(&close)
+                    .map(|i| pos + i + find_tag_end(&html[pos + i..end]))
+                    .unwrap_or(end);
+            } else if HTML_BLOCK_TAGS.contains(&name.as_str())
+                && !result.is_empty()
+                && !result.ends_with("\n\n")
+            {
+                result.push_str("\n\n");
+            }
+        } else {
+            let text_end = rest.find('<').unwrap_or(rest.len());
+            if note.is_none() {
+                push_html_text(&mut result, &decode_html_entities(&rest[..text_end]));
+            }
+            pos += text_end;
+        }
+    }
+
+    result.trim().to_string()
+}
+
+/// Byte length of the tag starting at `s[0] == '<'`, including the closing `>`.
+/// Quoted attribute values may contain `>`.
+fn find_tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Lowercase local name of a tag (namespace prefix stripped) and whether it's an end tag.
+/// Returns an empty name when `tag` isn't a tag at all.
+fn html_tag_name(tag: &str) -> (String, bool) {
+    let inner = tag.trim_start_matches('<');
+    let (inner, closing) = match inner.strip_prefix('/') {
+        Some(rest) => (rest, true),
+        None => (inner, false),
+    };
+    let name: String = inner
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == ':' || *c == '-')
+        .collect();
+    let name = name.rsplit(':').next().unwrap_or("").to_string();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        (name, closing)
+    } else {
+        (String::new(), closing)
+    }
+}
+
+/// Append text, collapsing whitespace runs into single spaces.
+fn push_html_text(result: &mut String, text: &str) {
+    if text.starts_with(char::is_whitespace) && !result.ends_with(char::is_whitespace) {
+        result.push(' ');
+    }
+    let mut words = text.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        result.push_str(word);
+        if words.peek().is_some() {
+            result.push(' ');
+        }
+    }
+    if text.ends_with(char::is_whitespace) && !text.trim().is_empty() {
+        result.push(' ');
+    }
+}
+
+/// HTML named entities for U+00A0 to U+00FF, in code point order
+const LATIN1_ENTITIES: [&str; 96] = [
+    "nbsp", "iexcl", "cent", "pound", "curren", "yen", "brvbar", "sect", "uml", "copy", "ordf", "laquo", "not", "shy", "reg", "macr",
+    "deg", "plusmn", "sup2", "sup3", "acute", "micro", "para", "middot", "cedil", "sup1", "ordm", "raquo", "frac14", "frac12", "frac34",
+    "iquest", "Agrave", "Aacute", "Acirc", "Atilde", "Auml", "Aring", "AElig", "Ccedil", "Egrave", "Eacute", "Ecirc", "Euml", "Igrave",
+    "Iacute", "Icirc", "Iuml", "ETH", "Ntilde", "Ograve", "Oacute", "Ocirc", "Otilde", "Ouml", "times", "Oslash", "Ugrave", "Uacute",
+    "Ucirc", "Uuml", "Yacute", "THORN", "szlig", "agrave", "aacute", "acirc", "atilde", "auml", "aring", "aelig", "ccedil", "egrave",
+    "eacute", "ecirc", "euml", "igrave", "iacute", "icirc", "iuml", "eth", "ntilde", "ograve", "oacute", "ocirc", "otilde", "ouml",
+    "divide", "oslash", "ugrave", "uacute", "ucirc", "uuml", "yacute", "thorn", "yuml",
+];
+const LATIN1_ENTITIES_START: u32 = 0xA0;
+
+/// Decode character references, the Latin-1 named entities and the common
+/// typographic ones. Unknown entities are kept verbatim.
+pub(crate) fn decode_html_entities(text: &str) -> String {
+    decode_entities_with(text, |entity| match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        "OElig" => Some('Œ'),
+        "oelig" => Some('œ'),
+        "euro" => Some('€'),
+        "trade" => Some('™'),
+        "bull" => Some('•'),
+        "dagger" => Some('†'),
+        "sbquo" => Some('‚'),
+        "bdquo" => Some('„'),
+        "mdash" => Some('—'),
+        "ndash" => Some('–'),
+        "hellip" => Some('…'),
+        "lsquo" => Some('‘'),
+        "rsquo" => Some('’'),
+        "ldquo" => Some('“'),
+        "rdquo" => Some('”'),
+        _ => LATIN1_ENTITIES
+            .iter()
+            .position(|&name| name == entity)
+            .and_then(|i| char::from_u32(LATIN1_ENTITIES_START + i as u32))
+            .or_else(|| decode_char_ref(entity)),
+    })
+}
+
+/// Decode only numeric character references (`&#8220;`, `&#x2014;`),
+/// leaving named entities such as `&amp;` alone.
+pub(crate) fn decode_char_refs(text: &str) -> String {
+    decode_entities_with(text, decode_char_ref)
+}
+
+/// Character for the body of a numeric reference: `#8220` or `#x201C`.
+fn decode_char_ref(entity: &str) -> Option<char> {
+    entity
+        .strip_prefix("#x")
+        .or_else(|| entity.strip_prefix("#X"))
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+        .and_then(char::from_u32)
+}
+
+/// Replace each `&name;` that `decode` knows with its character.
+fn decode_entities_with(text: &str, decode: impl Fn(&str) -> Option<char>) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest
+            .find(';')
+            .filter(|&semi| semi <= 10)
+            .and_then(|semi| decode(&rest[1..semi]).map(|c| (c, semi)));
+
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Create chapter from title and paragraphs with a tokenizer config.
+/// For just a different chunk size use `TokenizeConfig::with_max_chunk_chars`.
 pub fn create_chapter_with_config(
     index: u32,
     title: String,
     paragraphs: &[&str],
-    max_chunk_chars: usize,
+    config: &TokenizeConfig,
 ) -> crate::types::Chapter {
-    let words = tokenize_paragraphs_with_config(paragraphs, max_chunk_chars);
+    let mut words = Vec::new();
+    let mut ranges = Vec::new();
+    for_each_paragraph_group(paragraphs.iter().copied(), config, |group| {
+        ranges.push(Paragraph { word_start_offset: words.len() as u32, word_count: group.len() as u32 });
+        words.extend(group);
+    });
     let stats = ChapterStats::from_words(&words);
 
     crate::types::Chapter {
         index,
         title,
         words,
+        paragraphs: ranges,
         stats,
+        footnotes: Vec::new(),
+        global_word_start: 0,
+        audio_duration_ms: None,
+        nesting_level: 0,
+        manifest_id: None,
     }
 }
 
+/// Stats for a slice of words, e.g. `&chapter.words[start..end]`.
+/// Pair with `ChapterStats::subtract` for O(1) queries over prefix stats.
+pub fn stats_for_range(words: &[Word]) -> ChapterStats {
+    ChapterStats::from_words(words)
+}
+
 /// Create chapter with default chunk size.
 pub fn create_chapter(index: u32, title: String, paragraphs: &[&str]) -> crate::types::Chapter {
-    create_chapter_with_config(index, title, paragraphs, DEFAULT_MAX_CHUNK_CHARS)
+    create_chapter_with_config(index, title, paragraphs, &TokenizeConfig::default())
 }
 
 #[cfg(test)]
@@ -293,7 +1097,15 @@ mod tests {
         assert_eq!(words[0].text, "Hello,");
         assert_eq!(words[0].following_punct, Punctuation::Comma);
         assert_eq!(words[1].text, "world!");
-        assert_eq!(words[1].following_punct, Punctuation::Period);
+        assert_eq!(words[1].following_punct, Punctuation::Exclamation);
+    }
+
+    #[test]
+    fn test_sentence_end_punctuation() {
+        let words = tokenize("Stop. Really? Yes!");
+        assert_eq!(words[0].following_punct, Punctuation::Period);
+        assert_eq!(words[1].following_punct, Punctuation::Question);
+        assert_eq!(words[2].following_punct, Punctuation::Exclamation);
     }
 
     #[test]
@@ -304,6 +1116,18 @@ mod tests {
         assert_eq!(words[2].length_bucket, LengthBucket::Medium); // "reading" = 7 chars
     }
 
+    #[test]
+    fn test_cjk_length_buckets() {
+        // 3 ideographs = 6 display columns -> Medium, not Short
+        assert_eq!(LengthBucket::from_display_chars("日本語"), LengthBucket::Medium);
+        assert_eq!(LengthBucket::from_display_chars("word"), LengthBucket::Short);
+
+        // Long CJK runs are kept whole rather than split at byte offsets
+        let words = tokenize("日本語のテキストです");
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].length_bucket, LengthBucket::VeryLong); // 20 columns
+    }
+
     #[test]
     fn test_paragraph_marking() {
         let paragraphs = vec!["First paragraph", "Second paragraph"];
@@ -315,16 +1139,222 @@ mod tests {
         assert_eq!(words[3].following_punct, Punctuation::None);
     }
 
+    #[test]
+    fn test_dashes_split_words() {
+        for text in ["wait—what", "wait – what", "wait--what", "wait — what"] {
+            let words = tokenize(text);
+            let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+            assert_eq!(texts, vec!["wait", "what"], "{}", text);
+            assert_eq!(words[0].following_punct, Punctuation::Comma);
+            assert_eq!(words[1].following_punct, Punctuation::None);
+        }
+
+        // A stronger mark before the dash is kept; a leading dash is dropped
+        let words = tokenize("—Stop!—he said");
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["Stop!", "he", "said"]);
+        assert_eq!(words[0].following_punct, Punctuation::Exclamation);
+
+        // Plain hyphens are untouched
+        assert_eq!(tokenize("well-known")[0].text, "well-known");
+    }
+
+    #[test]
+    fn test_split_into_sentences() {
+        let config = TokenizeConfig {
+            paragraph_pause_mode: ParagraphPauseMode::ProportionalToLength,
+            ..Default::default()
+        };
+        let words = tokenize_paragraphs_with_config(
+            &["Chapter One", "It was late. Was it? Yes! The internationalization team, tired, went home"],
+            &config,
+        );
+        assert!(words.iter().any(Word::is_pause));
+
+        let sentences = split_into_sentences(&words);
+        let texts: Vec<Vec<&str>> = sentences.iter().map(|s| s.iter().map(|w| w.text.as_str()).collect()).collect();
+        assert_eq!(texts[0], vec!["Chapter", "One"]);
+        assert_eq!(texts[1], vec!["It", "was", "late."]);
+        assert_eq!(texts[2], vec!["Was", "it?"]);
+        assert_eq!(texts[3], vec!["Yes!"]);
+        assert_eq!(texts.len(), 5);
+        assert_eq!(texts[4].last(), Some(&"home"));
+
+        let counts = sentence_word_counts(&words);
+        assert_eq!(counts, sentences.iter().map(|s| s.len() as u32).collect::<Vec<_>>());
+        assert_eq!(counts[..4], [2, 3, 2, 1]);
+        assert!(split_into_sentences(&[]).is_empty());
+        assert_eq!(sentence_word_counts(&tokenize("no ending")), vec![2]);
+    }
+
+    #[test]
+    fn test_tokenize_with_stats() {
+        let (words, stats) = tokenize_with_stats("The quick brown fox jumps.");
+        assert_eq!(stats.split_words, 0);
+        assert_eq!(stats.total_tokens, 5);
+        assert_eq!(stats.total_chars, 22);
+        assert_eq!(stats.avg_token_length, 4.4);
+        assert_eq!(words.len(), 5);
+
+        let (words, stats) = tokenize_with_stats("An internationalization effort, incomprehensibly.");
+        assert_eq!(stats.split_words, 2);
+        assert_eq!(stats.total_tokens as usize, words.len());
+        assert_eq!(stats.total_chars as usize, words.iter().map(|w| w.text.chars().count()).sum::<usize>());
+
+        assert_eq!(tokenize_with_stats("").1, TokenizerStats::default());
+    }
+
+    #[test]
+    fn test_urls_and_emails_not_split() {
+        let words = tokenize("See https://example.com/some/internationalization/path or mail editor@publisher.example.org.");
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["See", "https://example.com/some/internationalization/path", "or", "mail", "editor@publisher.example.org."]
+        );
+        for word in [&words[1], &words[4]] {
+            assert!(word.is_url);
+            assert!(!word.is_chunk);
+            assert_eq!(word.length_bucket, LengthBucket::VeryLong);
+        }
+        assert_eq!(words[4].following_punct, Punctuation::Period);
+        assert!(!words[0].is_url);
+
+        assert!(tokenize("(www.example.com)")[0].is_url);
+        for text in ["httpd", "https://", "@handle", "a@b", "user@host."] {
+            assert!(!tokenize(text)[0].is_url, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_split_preserves_case() {
+        let texts = |word: &str| tokenize(word).into_iter().map(|w| w.text).collect::<Vec<_>>();
+        assert_eq!(texts("INTERNATIONALIZATION"), vec!["INTER-", "-NATIONAL-", "-IZATION"]);
+        assert_eq!(texts("InterNationalization"), vec!["Inter-", "-National-", "-ization"]);
+        assert_eq!(texts("internationalization"), vec!["inter-", "-national-", "-ization"]);
+
+        // A letter whose lowercase form has a different byte length must not
+        // shift the cut: the Kelvin sign (3 bytes, lowercase 'k' is 1) used
+        // to produce "KI-" here
+        let kelvin = "\u{212A}ILOMETERSTANDARDIZATION";
+        let chunks = texts(kelvin);
+        assert_eq!(chunks[0], "\u{212A}ILOMETERS-");
+        assert_eq!(chunks.iter().map(|c| c.trim_matches('-')).collect::<String>(), kelvin);
+    }
+
+    #[test]
+    fn test_split_keeps_apostrophes() {
+        let words = tokenize("grandchildren's toys");
+        let chunks: Vec<&str> = words.iter().take_while(|w| w.is_chunk).map(|w| w.text.as_str()).collect();
+        assert!(chunks.len() > 1);
+        let rejoined: String = chunks.iter().map(|c| c.trim_matches('-')).collect();
+        assert_eq!(rejoined, "grandchildren's");
+        assert_eq!(words.last().unwrap().text, "toys");
+    }
+
+    #[test]
+    fn test_count_syllables() {
+        for (word, expected) in [
+            ("the", 1),
+            ("a", 1),
+            ("reading", 2),
+            ("beautiful", 3),
+            ("pneumonia", 4),
+            ("rhythm", 2),
+            ("prism", 2),
+            ("table", 2),
+            ("make", 1),
+            ("media", 3),
+            ("special", 2),
+            ("nation", 2),
+            ("Hello,", 2),
+            ("42", 1),
+        ] {
+            assert_eq!(count_syllables(word), expected, "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_tokenize_paragraphs_indexed() {
+        let paragraphs = ["First paragraph here.", "", "Second.", "Third one"];
+        let (words, offsets) = tokenize_paragraphs_indexed(&paragraphs);
+        assert_eq!(offsets, vec![0, 3, 4]);
+        assert_eq!(words[offsets[1] as usize].text, "Second.");
+        assert_eq!(words.len(), tokenize_paragraphs(&paragraphs).len());
+
+        let chapter = create_chapter(0, "C".to_string(), &paragraphs);
+        assert_eq!(chapter.paragraph_offsets().collect::<Vec<_>>(), offsets);
+    }
+
+    #[test]
+    fn test_tokenize_paragraphs_iter_matches_slice() {
+        let text = "Short one\n\nA longer second paragraph here.\n\nLast";
+        let config = TokenizeConfig {
+            paragraph_pause_mode: ParagraphPauseMode::ProportionalToLength,
+            ..TokenizeConfig::default()
+        };
+        let paragraphs: Vec<&str> = text.split("\n\n").collect();
+        let expected = tokenize_paragraphs_with_config(&paragraphs, &config);
+        let streamed = tokenize_paragraphs_iter_with_config(text.split("\n\n"), &config);
+
+        let texts = |words: &[Word]| words.iter().map(|w| (w.text.clone(), w.following_punct)).collect::<Vec<_>>();
+        assert_eq!(texts(&streamed), texts(&expected));
+        assert!(streamed[2].is_pause());
+        assert_eq!(streamed.last().unwrap().following_punct, Punctuation::None);
+        assert!(tokenize_paragraphs_iter(std::iter::empty()).is_empty());
+    }
+
+    #[test]
+    fn test_decode_latin1_entities() {
+        assert_eq!(decode_html_entities("&iexcl;Se&ntilde;or! &Uuml;ber &yuml; &copy;"), "¡Señor! Über ÿ ©");
+        assert_eq!(decode_html_entities("&frac12; &times; 2 &euro;"), "½ × 2 €");
+        // Entity names are case-sensitive
+        assert_eq!(decode_html_entities("&EACUTE; &Eacute;"), "&EACUTE; É");
+    }
+
+    #[test]
+    fn test_tokenize_html_malformed() {
+        // Unclosed <p>, <br> and <img>, stray end tag, HTML-only entity
+        let html = b"<html><head><title>T</title></head><body>\
+            <p>First&nbsp;paragraph<br>continues\
+            <p>Second <img src='x.png'> one</span>\
+            <script>var x = '<p>';</script>\
+            <p>Caf&eacute; caf&#233; &#x43;at</body></html>";
+        let words = tokenize_html(html);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec!["First", "paragraph", "continues", "Second", "one", "Café", "café", "Cat"]
+        );
+        assert_eq!(words[1].following_punct, Punctuation::Paragraph);
+        assert_eq!(words[4].following_punct, Punctuation::Paragraph);
+    }
+
+    #[test]
+    fn test_tokenize_html_inline_tags() {
+        let words = tokenize_html(b"<p>re<i>read</i>s <b>news</b>.</p>");
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, vec!["rereads", "news."]);
+    }
+
+    #[test]
+    fn test_unique_word_count() {
+        let words = tokenize("The cat saw the dog. \"The\" dog saw internationalization, internationalization!");
+        // the, cat, saw, dog, internationalization
+        assert_eq!(unique_word_count(&words), 5);
+        assert_eq!(unique_word_count(&[]), 0);
+    }
+
     #[test]
     fn test_split_long_word_short_word() {
         // Short words should not be split
-        let chunks = split_long_word("reading", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("reading", &TokenizeConfig::default());
         assert_eq!(chunks, vec!["reading"]);
     }
 
     #[test]
     fn test_split_long_word_with_prefix() {
-        let chunks = split_long_word("internationalization", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("internationalization", &TokenizeConfig::default());
         assert_eq!(chunks.len(), 3);
         assert_eq!(chunks[0], "inter-");
         assert!(chunks[1].starts_with("-") || !chunks[1].starts_with("-")); // middle chunk
@@ -334,7 +1364,7 @@ mod tests {
     #[test]
     fn test_split_long_word_with_suffix() {
         // "unbelievable" is 12 chars, >= MIN_SPLIT_LENGTH (11), so it IS split
-        let chunks = split_long_word("unbelievable", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("unbelievable", &TokenizeConfig::default());
         assert!(chunks.len() >= 2, "12-char word should be split");
         // Verify chunks fit DEFAULT_MAX_CHUNK_CHARS
         for chunk in &chunks {
@@ -346,7 +1376,7 @@ mod tests {
     #[test]
     fn test_split_word_with_both_affixes() {
         // "unbelievability" is 15 chars, should be split
-        let chunks = split_long_word("unbelievability", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("unbelievability", &TokenizeConfig::default());
         assert!(chunks.len() >= 2, "15-char word should be split");
         assert_eq!(chunks[0], "un-");
     }
@@ -363,10 +1393,23 @@ mod tests {
         assert_eq!(words.last().unwrap().following_punct, Punctuation::Period);
     }
 
+    #[test]
+    fn test_chunk_roles() {
+        let words = tokenize("The internationalization works");
+        let roles: Vec<ChunkRole> = words.iter().map(|w| w.chunk_role).collect();
+        assert_eq!(
+            roles,
+            vec![ChunkRole::Whole, ChunkRole::First, ChunkRole::Middle, ChunkRole::Last, ChunkRole::Whole]
+        );
+        assert!(!words[0].is_chunk);
+        assert!(words[1..4].iter().all(|w| w.is_chunk));
+        assert!(!words[4].is_chunk);
+    }
+
     #[test]
     fn test_split_extreme_word() {
         // 45 chars - should definitely be split
-        let chunks = split_long_word("pneumonoultramicroscopicsilicovolcanoconiosis", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("pneumonoultramicroscopicsilicovolcanoconiosis", &TokenizeConfig::default());
         assert!(chunks.len() >= 3);
         // Each chunk should be <= DEFAULT_MAX_CHUNK_CHARS + 2 (for hyphens)
         for chunk in &chunks {
@@ -379,7 +1422,7 @@ mod tests {
     fn test_no_split_short_word() {
         // "comprehension" is 13 chars, exactly at MIN_SPLIT_LENGTH
         // Should NOT be split (13 < 13 is false, so it stays as-is)
-        let chunks = split_long_word("comprehension", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("comprehension", &TokenizeConfig::default());
         // 13 chars is at the boundary - test the actual behavior
         assert!(!chunks.is_empty());
     }
@@ -387,7 +1430,7 @@ mod tests {
     #[test]
     fn test_split_14_char_word() {
         // "infrastructure" is 14 chars, should be split with default (10)
-        let chunks = split_long_word("infrastructure", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("infrastructure", &TokenizeConfig::default());
         assert!(chunks.len() >= 2, "14-char word should be split with max_chunk_chars=10");
     }
 
@@ -396,17 +1439,17 @@ mod tests {
         // Test scientific/technical terms with expanded affixes
 
         // "neuropsychological" - neuro + psychological
-        let chunks = split_long_word("neuropsychological", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("neuropsychological", &TokenizeConfig::default());
         assert!(chunks.len() >= 2);
         assert!(chunks[0].starts_with("neuro"), "Should detect 'neuro' prefix, got: {:?}", chunks);
 
         // "electroencephalography" - electro + encephalography
-        let chunks = split_long_word("electroencephalography", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("electroencephalography", &TokenizeConfig::default());
         assert!(chunks.len() >= 2);
         assert!(chunks[0].starts_with("electro"), "Should detect 'electro' prefix, got: {:?}", chunks);
 
         // "biodegradability" - bio + degradability
-        let chunks = split_long_word("biodegradability", DEFAULT_MAX_CHUNK_CHARS);
+        let chunks = Tokenizer::english().split_long_word("biodegradability", &TokenizeConfig::default());
         assert!(chunks.len() >= 2);
         assert!(chunks[0].starts_with("bio"), "Should detect 'bio' prefix, got: {:?}", chunks);
     }
@@ -422,7 +1465,7 @@ mod tests {
         ];
 
         for word in long_words {
-            let chunks = split_long_word(word, DEFAULT_MAX_CHUNK_CHARS);
+            let chunks = Tokenizer::english().split_long_word(word, &TokenizeConfig::default());
             for chunk in &chunks {
                 let clean_len: usize = chunk.chars().filter(|c| c.is_alphabetic()).count();
                 assert!(
@@ -434,15 +1477,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_min_chunk_chars_round_trip() {
+        // A word of exactly min_chunk_chars + 1 letters, split with the smallest
+        // possible max, must reassemble to the original word
+        for min_chunk_chars in [1, 2, DEFAULT_MIN_CHUNK_CHARS, 5] {
+            let word: String = "abcdefghij".chars().take(min_chunk_chars + 1).collect();
+            let config = TokenizeConfig {
+                min_chunk_chars,
+                ..TokenizeConfig::with_max_chunk_chars(min_chunk_chars)
+            };
+            let chunks = Tokenizer::english().split_long_word(&word, &config);
+            let rejoined: String = chunks.concat().replace('-', "");
+            assert_eq!(rejoined, word, "min_chunk_chars={} chunks={:?}", min_chunk_chars, chunks);
+        }
+
+        // Affixes are only split off if at least min_chunk_chars letters remain:
+        // "unhappiness" = "un" + 9 letters
+        let chunks = Tokenizer::english().split_long_word("unhappiness", &TokenizeConfig::default());
+        assert_eq!(chunks[0], "un-");
+        let config = TokenizeConfig {
+            min_chunk_chars: 9,
+            ..TokenizeConfig::default()
+        };
+        let chunks = Tokenizer::english().split_long_word("unhappiness", &config);
+        assert_ne!(chunks[0], "un-");
+    }
+
+    #[test]
+    fn test_tokenize_config() {
+        let text = "The infrastructure is important.";
+
+        let defaults = TokenizeConfig::default();
+        assert_eq!(defaults.max_chunk_chars, DEFAULT_MAX_CHUNK_CHARS);
+        assert_eq!(tokenize(text).len(), tokenize_with_config(text, &defaults).len());
+
+        let no_split = TokenizeConfig {
+            split_enabled: false,
+            ..TokenizeConfig::default()
+        };
+        assert_eq!(tokenize_with_config(text, &no_split).len(), 4);
+
+        // Raising min_split_length keeps the 14-letter word whole at max=10
+        let high_threshold = TokenizeConfig {
+            min_split_length: 15,
+            ..TokenizeConfig::default()
+        };
+        assert!(tokenize_with_config(text, &high_threshold)
+            .iter()
+            .any(|w| w.text == "infrastructure"));
+    }
+
     #[test]
     fn test_configurable_chunk_size() {
         // With max_chunk_chars=20, "infrastructure" (14 chars) should NOT be split
-        let chunks = split_long_word("infrastructure", 20);
+        let chunks = Tokenizer::english().split_long_word("infrastructure", &TokenizeConfig::with_max_chunk_chars(20));
         assert_eq!(chunks.len(), 1, "14-char word should not be split with max_chunk_chars=20");
         assert_eq!(chunks[0], "infrastructure");
 
         // With max_chunk_chars=10, "infrastructure" SHOULD be split
-        let chunks = split_long_word("infrastructure", 10);
+        let chunks = Tokenizer::english().split_long_word("infrastructure", &TokenizeConfig::with_max_chunk_chars(10));
         assert!(chunks.len() >= 2, "14-char word should be split with max_chunk_chars=10");
     }
 
@@ -452,23 +1546,23 @@ mod tests {
 
         // 11-char word "comfortable" - should split at max=10, not at max=11+
         let word = "comfortable"; // 11 chars
-        assert!(split_long_word(word, 10).len() >= 2, "11-char word should split at max=10");
-        assert_eq!(split_long_word(word, 11).len(), 1, "11-char word should NOT split at max=11");
-        assert_eq!(split_long_word(word, 12).len(), 1, "11-char word should NOT split at max=12");
+        assert!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(10)).len() >= 2, "11-char word should split at max=10");
+        assert_eq!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(11)).len(), 1, "11-char word should NOT split at max=11");
+        assert_eq!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(12)).len(), 1, "11-char word should NOT split at max=12");
 
         // 15-char word "internationally" - should split at max<=14
         let word = "internationally"; // 15 chars
-        assert!(split_long_word(word, 10).len() >= 2, "15-char word should split at max=10");
-        assert!(split_long_word(word, 14).len() >= 2, "15-char word should split at max=14");
-        assert_eq!(split_long_word(word, 15).len(), 1, "15-char word should NOT split at max=15");
+        assert!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(10)).len() >= 2, "15-char word should split at max=10");
+        assert!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(14)).len() >= 2, "15-char word should split at max=14");
+        assert_eq!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(15)).len(), 1, "15-char word should NOT split at max=15");
 
         // 20-char word "internationalization" - test across range
         let word = "internationalization"; // 20 chars
-        assert!(split_long_word(word, 10).len() >= 2, "20-char should split at max=10");
-        assert!(split_long_word(word, 15).len() >= 2, "20-char should split at max=15");
-        assert!(split_long_word(word, 19).len() >= 2, "20-char should split at max=19");
-        assert_eq!(split_long_word(word, 20).len(), 1, "20-char should NOT split at max=20");
-        assert_eq!(split_long_word(word, 22).len(), 1, "20-char should NOT split at max=22");
+        assert!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(10)).len() >= 2, "20-char should split at max=10");
+        assert!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(15)).len() >= 2, "20-char should split at max=15");
+        assert!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(19)).len() >= 2, "20-char should split at max=19");
+        assert_eq!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(20)).len(), 1, "20-char should NOT split at max=20");
+        assert_eq!(Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(22)).len(), 1, "20-char should NOT split at max=22");
     }
 
     #[test]
@@ -477,7 +1571,7 @@ mod tests {
         let word = "internationalization"; // 20 chars
 
         for max_chars in [10, 12, 15, 18] {
-            let chunks = split_long_word(word, max_chars);
+            let chunks = Tokenizer::english().split_long_word(word, &TokenizeConfig::with_max_chunk_chars(max_chars));
             for chunk in &chunks {
                 let clean_len: usize = chunk.chars().filter(|c| c.is_alphabetic()).count();
                 assert!(
@@ -495,15 +1589,92 @@ mod tests {
         let text = "The infrastructure is important.";
 
         // With max=20, "infrastructure" stays intact
-        let words = tokenize_with_config(text, 20);
+        let words = tokenize_with_config(text, &TokenizeConfig::with_max_chunk_chars(20));
         assert!(words.iter().any(|w| w.text == "infrastructure"),
             "With max=20, 'infrastructure' should not be split");
 
         // With max=10, "infrastructure" gets split
-        let words = tokenize_with_config(text, 10);
+        let words = tokenize_with_config(text, &TokenizeConfig::with_max_chunk_chars(10));
         assert!(!words.iter().any(|w| w.text == "infrastructure"),
             "With max=10, 'infrastructure' should be split");
         assert!(words.iter().any(|w| w.text.contains("infra")),
             "Should have chunk containing 'infra'");
     }
+
+    #[test]
+    fn test_german_tokenizer_uses_german_affixes() {
+        let german = Tokenizer::german();
+        let chunks = german.split_long_word("Verantwortlichkeit", &TokenizeConfig::default());
+        assert_eq!(chunks.first().map(String::as_str), Some("Ver-"));
+        assert_eq!(chunks.last().map(String::as_str), Some("-keit"));
+        assert_eq!(chunks.concat().replace('-', ""), "Verantwortlichkeit");
+
+        // English tables don't know "keit"
+        let english = Tokenizer::english().split_long_word("Verantwortlichkeit", &TokenizeConfig::default());
+        assert_ne!(english.last(), chunks.last());
+    }
+
+    #[test]
+    fn test_split_long_word_non_ascii_middle() {
+        // Chunking counts chars, so "ß" and "ü" are never cut mid-byte
+        let words = Tokenizer::german().tokenize("Straßenbahnhaltestellenüberdachung.");
+        assert!(words.len() > 1);
+        let joined: String = words.iter().map(|w| w.text.trim_matches('-')).collect();
+        assert_eq!(joined, "Straßenbahnhaltestellenüberdachung");
+        assert_eq!(words.last().unwrap().following_punct, Punctuation::Period);
+    }
+
+    #[test]
+    fn test_free_functions_match_english_tokenizer() {
+        let text = "Internationalization is unbelievable.";
+        let texts = |words: Vec<Word>| words.into_iter().map(|w| w.text).collect::<Vec<_>>();
+        assert_eq!(texts(tokenize(text)), texts(Tokenizer::english().tokenize(text)));
+    }
+
+    #[test]
+    fn test_paragraph_pause_modes() {
+        let paragraphs = ["Chapter One", "It was a dark night.", "Yes.", "The end came."];
+        let count_pauses = |mode| {
+            let config = TokenizeConfig {
+                paragraph_pause_mode: mode,
+                ..TokenizeConfig::default()
+            };
+            let words = tokenize_paragraphs_with_config(&paragraphs, &config);
+            let stats = ChapterStats::from_words(&words);
+            assert_eq!(stats.word_count, 11, "pause words aren't counted");
+            words.iter().filter(|w| w.is_pause()).count()
+        };
+
+        assert_eq!(count_pauses(ParagraphPauseMode::Uniform), 0);
+        // "Chapter One" and "Yes." are short; the last paragraph never gets one
+        assert_eq!(count_pauses(ParagraphPauseMode::ProportionalToLength), 2);
+        // "Yes." ends a sentence, so only the heading pauses
+        assert_eq!(count_pauses(ParagraphPauseMode::HeadingDetected), 1);
+
+        let config = TokenizeConfig {
+            paragraph_pause_mode: ParagraphPauseMode::HeadingDetected,
+            ..TokenizeConfig::default()
+        };
+        let words = tokenize_paragraphs_with_config(&paragraphs, &config);
+        assert_eq!(words[1].text, "One");
+        assert_eq!(words[1].following_punct, Punctuation::Paragraph);
+        assert!(words[2].is_pause());
+        assert_eq!(words[2].following_punct, Punctuation::Paragraph);
+    }
+
+    #[test]
+    fn test_chapter_paragraphs() {
+        let chapter = create_chapter(0, "P".to_string(), &["The quick brown fox.", "", "Jumps over", "the dog."]);
+        assert_eq!(chapter.paragraphs.len(), 3);
+
+        let starts: Vec<u32> = chapter.paragraph_offsets().collect();
+        assert_eq!(starts, vec![0, 4, 6]);
+        // Grouped by source paragraph even when it ends in a sentence mark
+        assert_eq!(chapter.paragraph_words(0).unwrap().last().unwrap().text, "fox.");
+        assert_eq!(chapter.paragraph_words(1).unwrap()[1].following_punct, Punctuation::Paragraph);
+
+        let flattened: Vec<&str> = (0..chapter.paragraphs.len()).flat_map(|i| chapter.paragraph_words(i).unwrap()).map(|w| w.text.as_str()).collect();
+        let words: Vec<&str> = chapter.words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(flattened, words);
+    }
 }
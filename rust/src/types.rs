@@ -1,8 +1,12 @@
 //! Core types for the Spread parser.
 //! These mirror the Kotlin domain types.
 
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use thiserror::Error;
+
 /// Length bucket for adaptive timing
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum LengthBucket {
     Short = 0,     // 1-4 chars
@@ -12,6 +16,7 @@ pub enum LengthBucket {
 }
 
 impl LengthBucket {
+    #[inline]
     pub fn from_length(len: usize) -> Self {
         match len {
             0..=4 => LengthBucket::Short,
@@ -20,106 +25,1965 @@ impl LengthBucket {
             _ => LengthBucket::VeryLong,
         }
     }
+
+    /// Bucket by rendered width rather than codepoint count.
+    ///
+    /// Only letters, digits, apostrophes and hyphens are counted (as in
+    /// `from_length`), but East Asian wide characters count as two columns,
+    /// so a 3-character Japanese word lands in the same bucket as a
+    /// 6-letter English word. With the `cjk` feature the width comes from
+    /// the Unicode East Asian Width property; otherwise a built-in table of
+    /// the main CJK blocks is used.
+    pub fn from_display_chars(text: &str) -> Self {
+        let width: usize = text
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '\'' || *c == '-')
+            .map(display_width)
+            .sum();
+        Self::from_length(width)
+    }
+
+    /// Display-time multiplier for words in this bucket, relative to the
+    /// base word duration; see `LENGTH_MULTIPLIERS` (SYNC: Timing.kt).
+    #[inline]
+    pub fn display_multiplier(self) -> f32 {
+        LENGTH_MULTIPLIERS[self as usize]
+    }
+}
+
+/// Display columns taken by a single character.
+#[cfg(feature = "cjk")]
+#[inline]
+fn display_width(c: char) -> usize {
+    unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+/// Display columns taken by a single character.
+#[cfg(not(feature = "cjk"))]
+#[inline]
+fn display_width(c: char) -> usize {
+    if is_wide_char(c) {
+        2
+    } else {
+        1
+    }
+}
+
+/// True for characters in the main CJK (wide) Unicode blocks.
+#[inline]
+pub fn is_wide_char(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F     // Hangul Jamo
+        | 0x2E80..=0x303E   // CJK radicals, Kangxi, CJK symbols
+        | 0x3041..=0x33FF   // Hiragana, Katakana, Bopomofo, CJK compat
+        | 0x3400..=0x4DBF   // CJK Extension A
+        | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+        | 0xA000..=0xA4CF   // Yi
+        | 0xAC00..=0xD7A3   // Hangul syllables
+        | 0xF900..=0xFAFF   // CJK compatibility ideographs
+        | 0xFE30..=0xFE4F   // CJK compatibility forms
+        | 0xFF00..=0xFF60   // Fullwidth forms
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD // CJK Extensions B+
+    )
 }
 
 /// Punctuation type for adaptive timing
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(u8)]
 pub enum Punctuation {
     None = 0,
-    Comma = 1,      // , ; :
-    Period = 2,     // . ! ?
-    Paragraph = 3,  // paragraph break
+    Comma = 1,        // , ; :
+    Period = 2,       // .
+    Exclamation = 3,  // !
+    Question = 4,     // ?
+    Paragraph = 5,    // paragraph break
 }
 
+/// Number of `Punctuation` variants (length of `ChapterStats::punct_counts`).
+pub const PUNCT_VARIANTS: usize = 6;
+
 impl Punctuation {
+    #[inline]
     pub fn from_char(c: char) -> Self {
         match c {
-            '.' | '!' | '?' => Punctuation::Period,
+            '.' => Punctuation::Period,
+            '!' => Punctuation::Exclamation,
+            '?' => Punctuation::Question,
             ',' | ';' | ':' => Punctuation::Comma,
             _ => Punctuation::None,
         }
     }
+
+    /// Representative character for the mark, `None` for `None` and `Paragraph`.
+    #[inline]
+    pub fn as_char(self) -> Option<char> {
+        match self {
+            Punctuation::Comma => Some(','),
+            Punctuation::Period => Some('.'),
+            Punctuation::Exclamation => Some('!'),
+            Punctuation::Question => Some('?'),
+            Punctuation::None | Punctuation::Paragraph => None,
+        }
+    }
+
+    /// Extra pause after this mark in ms; see `PUNCT_PAUSE_MS` (SYNC:
+    /// Timing.kt). The same at every reading speed.
+    #[inline]
+    pub fn pause_ms(self) -> u32 {
+        PUNCT_PAUSE_MS[self as usize]
+    }
+}
+
+/// Position of a word within a long word split by the tokenizer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum ChunkRole {
+    #[default]
+    Whole = 0,   // not split
+    First = 1,   // "inter-"
+    Middle = 2,  // "-nation-"
+    Last = 3,    // "-alization"
 }
 
 /// A single word with pre-computed metadata for O(1) timing calculation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Word {
     pub text: String,
     pub length_bucket: LengthBucket,
     pub following_punct: Punctuation,
+    /// True if this is a fragment of a split long word
+    pub is_chunk: bool,
+    pub chunk_role: ChunkRole,
+    /// True for a URL or email address, which is never split
+    pub is_url: bool,
+}
+
+/// Display-time multiplier per `LengthBucket`, approximating
+/// sqrt(avg bucket length / 5.2), where 5.2 is the average English word
+/// length: longer words take longer to recognise, but sub-linearly.
+///
+/// SYNC: Must match the `*_WORD_MULTIPLIER` constants in
+/// app/src/main/kotlin/app/spread/domain/Timing.kt
+pub const LENGTH_MULTIPLIERS: [f32; 4] = [0.76, 1.12, 1.42, 1.70];
+
+/// Extra pause after each `Punctuation` mark in ms, giving time to close a
+/// clause or sentence.
+///
+/// SYNC: Must match the delays of `TimingSettings.Natural` in
+/// app/src/main/kotlin/app/spread/domain/Timing.kt
+pub const PUNCT_PAUSE_MS: [u32; PUNCT_VARIANTS] = [0, 75, 150, 150, 150, 300];
+
+/// Display-time multiplier for fragments of a split long word, giving the
+/// reader time to put the word back together.
+///
+/// SYNC: Must match `DEFAULT_SPLIT_CHUNK_MULTIPLIER` in
+/// app/src/main/kotlin/app/spread/domain/Timing.kt
+pub const SPLIT_CHUNK_MULTIPLIER: f32 = 1.3;
+
+/// Milliseconds per minute, for converting words per minute to ms per word.
+pub const MS_PER_MINUTE: f32 = 60_000.0;
+
+/// Words on a typical printed paperback page, for `approximate_page_count`.
+pub const DEFAULT_WORDS_PER_PAGE: u32 = 250;
+
+/// Jaro-Winkler similarity for `Book::chapter_by_title_fuzzy` that tolerates
+/// a typo or a dropped word without matching unrelated titles.
+pub const DEFAULT_TITLE_SIMILARITY: f32 = 0.8;
+
+/// Pages needed for `words` at `words_per_page` (0 treated as 1), rounded up.
+fn page_count(words: u32, words_per_page: u32) -> u32 {
+    words.div_ceil(words_per_page.max(1))
+}
+
+impl Word {
+    /// Synthetic pause word inserted after short paragraphs
+    /// (see `tokenizer::ParagraphPauseMode`).
+    pub fn pause() -> Self {
+        Word {
+            text: String::new(),
+            length_bucket: LengthBucket::Short,
+            following_punct: Punctuation::Paragraph,
+            is_chunk: false,
+            chunk_role: ChunkRole::Whole,
+            is_url: false,
+        }
+    }
+
+    /// True for a synthetic pause word. Pause words have no text and are left
+    /// out of `ChapterStats` and global word offsets.
+    #[inline]
+    pub fn is_pause(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Byte offset in `text` of the Optimal Recognition Point, the character
+    /// the renderer aligns with the center marker.
+    ///
+    /// That is letter number `ceil(letters * 0.35)` (1-based), counting only
+    /// alphanumeric characters, so leading chunk hyphens and quotes are
+    /// skipped. 0 for a word without letters.
+    pub fn orp_offset(&self) -> usize {
+        let letters = self.text.chars().filter(|c| c.is_alphanumeric()).count();
+        let target = ((letters as f32 * 0.35).ceil() as usize).saturating_sub(1);
+        self.text
+            .char_indices()
+            .filter(|(_, c)| c.is_alphanumeric())
+            .nth(target)
+            .map_or(0, |(offset, _)| offset)
+    }
+
+    /// `text` without the punctuation around it: chunk hyphens, quotes and
+    /// trailing marks ("-national-" -> "national", "world!\"" -> "world").
+    /// Inner punctuation such as the apostrophe in "don't" is kept. Borrows
+    /// from `text`.
+    pub fn strip_punctuation_text(&self) -> &str {
+        self.text.trim_matches(|c: char| !c.is_alphanumeric())
+    }
+
+    /// Lowercased `strip_punctuation_text`, for comparisons and lookups.
+    pub fn clean_text(&self) -> String {
+        self.strip_punctuation_text().to_lowercase()
+    }
+
+    /// Estimated English syllables (see `tokenizer::count_syllables`), with
+    /// split-chunk hyphens removed first. 0 for a pause word.
+    pub fn syllable_count(&self) -> u32 {
+        if self.is_pause() {
+            return 0;
+        }
+        crate::tokenizer::count_syllables(self.text.trim_matches('-'))
+    }
+
+    /// How long to show this word at `base_wpm`, as Kotlin `Word.delayMs`
+    /// with the Natural preset: `MS_PER_MINUTE / base_wpm *
+    /// bucket.display_multiplier()`, times `SPLIT_CHUNK_MULTIPLIER` for
+    /// split-word fragments, plus `punct.pause_ms()`.
+    /// A `base_wpm` of 0 is treated as 1.
+    #[inline]
+    pub fn display_duration_ms(&self, base_wpm: u32) -> u32 {
+        let base_ms = MS_PER_MINUTE / base_wpm.max(1) as f32;
+        let chunk_multiplier = if self.is_chunk { SPLIT_CHUNK_MULTIPLIER } else { 1.0 };
+        let length_ms = (base_ms * self.length_bucket.display_multiplier() * chunk_multiplier).round() as u32;
+        length_ms + self.following_punct.pause_ms()
+    }
 }
 
 /// Pre-computed statistics for a chapter (enables O(1) effective WPM calculation)
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ChapterStats {
     pub word_count: u32,
     pub length_counts: [u32; 4],  // [short, medium, long, very_long]
-    pub punct_counts: [u32; 4],   // [none, comma, period, paragraph]
+    pub punct_counts: [u32; PUNCT_VARIANTS],  // [none, comma, period, exclamation, question, paragraph]
+    pub syllable_count: u32,      // estimated, see tokenizer::count_syllables
 }
 
 impl ChapterStats {
+    /// Stats over `words`; synthetic pause words are skipped.
     pub fn from_words(words: &[Word]) -> Self {
         let mut stats = ChapterStats::default();
-        stats.word_count = words.len() as u32;
-
         for word in words {
-            stats.length_counts[word.length_bucket as usize] += 1;
-            stats.punct_counts[word.following_punct as usize] += 1;
+            stats.add_word(word);
         }
-
         stats
     }
 
+    /// Count one more word, for building stats while streaming. Synthetic
+    /// pause words are skipped, as in `from_words`.
+    #[inline]
+    pub fn add_word(&mut self, word: &Word) {
+        if word.is_pause() {
+            return;
+        }
+        self.word_count += 1;
+        self.length_counts[word.length_bucket as usize] += 1;
+        self.punct_counts[word.following_punct as usize] += 1;
+        self.syllable_count += word.syllable_count();
+    }
+
+    /// Number of sentences, counted by sentence-ending punctuation.
+    /// Text without any (e.g. a lone heading) counts as one sentence.
+    pub fn sentence_count(&self) -> u32 {
+        let ends = self.punct_counts[Punctuation::Period as usize]
+            + self.punct_counts[Punctuation::Exclamation as usize]
+            + self.punct_counts[Punctuation::Question as usize];
+        if ends == 0 && self.word_count > 0 {
+            1
+        } else {
+            ends
+        }
+    }
+
+    /// Flesch-Kincaid grade level (US school grade). 0.0 for empty chapters.
+    pub fn flesch_kincaid_grade(&self) -> f32 {
+        if self.word_count == 0 {
+            return 0.0;
+        }
+        0.39 * self.words_per_sentence() + 11.8 * self.syllables_per_word() - 15.59
+    }
+
+    /// Flesch reading ease (higher is easier, ~0-100). 0.0 for empty chapters.
+    pub fn flesch_reading_ease(&self) -> f32 {
+        if self.word_count == 0 {
+            return 0.0;
+        }
+        206.835 - 1.015 * self.words_per_sentence() - 84.6 * self.syllables_per_word()
+    }
+
+    /// Time to read the chapter at `wpm` words per minute, in seconds.
+    /// 0.0 when `wpm` is 0.
+    pub fn estimated_reading_time_seconds(&self, wpm: u32) -> f32 {
+        if wpm == 0 {
+            return 0.0;
+        }
+        self.word_count as f32 * 60.0 / wpm as f32
+    }
+
+    /// Reading time at `wpm` as "3h 14m", "42m" or "< 1m".
+    pub fn estimated_reading_time_formatted(&self, wpm: u32) -> String {
+        format_reading_time(self.estimated_reading_time_seconds(wpm))
+    }
+
+    fn words_per_sentence(&self) -> f32 {
+        self.word_count as f32 / self.sentence_count() as f32
+    }
+
+    fn syllables_per_word(&self) -> f32 {
+        self.syllable_count as f32 / self.word_count as f32
+    }
+
+    #[inline]
     pub fn merge(&mut self, other: &ChapterStats) {
         self.word_count += other.word_count;
-        for i in 0..4 {
+        for i in 0..self.length_counts.len() {
             self.length_counts[i] += other.length_counts[i];
+        }
+        for i in 0..self.punct_counts.len() {
             self.punct_counts[i] += other.punct_counts[i];
         }
+        self.syllable_count += other.syllable_count;
+    }
+
+    /// Elementwise difference, the inverse of `merge`.
+    ///
+    /// With prefix stats (words `0..i` and `0..j`), `prefix_j.subtract(&prefix_i)`
+    /// gives the stats of words `i..j` without rescanning them. Counts saturate
+    /// at zero if `other` isn't contained in `self`.
+    pub fn subtract(&self, other: &ChapterStats) -> ChapterStats {
+        let mut result = self.clone();
+        result.word_count = result.word_count.saturating_sub(other.word_count);
+        for (count, sub) in result.length_counts.iter_mut().zip(&other.length_counts) {
+            *count = count.saturating_sub(*sub);
+        }
+        for (count, sub) in result.punct_counts.iter_mut().zip(&other.punct_counts) {
+            *count = count.saturating_sub(*sub);
+        }
+        result.syllable_count = result.syllable_count.saturating_sub(other.syllable_count);
+        result
+    }
+}
+
+/// A footnote or endnote, kept out of the RSVP word stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footnote {
+    /// `id` attribute, the target of the note reference link
+    pub id: String,
+    pub text: String,
+}
+
+/// One source paragraph, as a range of `Chapter::words`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paragraph {
+    /// Index of the first word in `Chapter::words`
+    pub word_start_offset: u32,
+    /// Number of words, including a trailing pause word
+    pub word_count: u32,
+}
+
+impl Paragraph {
+    /// The paragraph's indices in `Chapter::words`.
+    pub fn word_range(&self) -> Range<usize> {
+        let start = self.word_start_offset as usize;
+        start..start + self.word_count as usize
     }
 }
 
 /// A chapter in a book
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Chapter {
     pub index: u32,
     pub title: String,
+    /// Every word in reading order (all paragraphs flattened)
     pub words: Vec<Word>,
+    /// Source paragraphs, as ranges of `words`
+    pub paragraphs: Vec<Paragraph>,
     pub stats: ChapterStats,
+    pub footnotes: Vec<Footnote>,
+    /// Global word offset of this chapter's first word; equals
+    /// `book.stats.chapter_offsets[i]`. For `LazyBook` chapters see
+    /// `LazyBook::global_word_start`.
+    pub global_word_start: u32,
+    /// Read-aloud narration length from the chapter's Media Overlay, when
+    /// `ParseConfig::extract_audio_metadata` is set and the chapter has one.
+    pub audio_duration_ms: Option<u64>,
+    /// Depth of the chapter's entry in the table of contents (EPUB 3 nav or
+    /// NCX): 0 for top-level chapters (and chapters not in it), 1 for
+    /// sub-chapters, ...
+    pub nesting_level: u8,
+    /// Manifest id of the chapter's spine item (its `<itemref idref>`);
+    /// `None` for chapters not read from an EPUB spine.
+    pub manifest_id: Option<String>,
+}
+
+impl Chapter {
+    /// Index in `words` of each paragraph's first word.
+    pub fn paragraph_offsets(&self) -> impl Iterator<Item = u32> + '_ {
+        self.paragraphs.iter().map(|p| p.word_start_offset)
+    }
+
+    /// Words of the paragraph at `index`, or `None` if out of range.
+    pub fn paragraph_words(&self, index: usize) -> Option<&[Word]> {
+        self.words.get(self.paragraphs.get(index)?.word_range())
+    }
+
+    /// Type-token ratio: distinct words / total words, in [0, 1].
+    /// Computed on demand; 0.0 for an empty chapter.
+    pub fn vocabulary_richness(&self) -> f32 {
+        let mut vocabulary = HashSet::new();
+        let total = crate::tokenizer::collect_vocabulary(&self.words, &mut vocabulary);
+        if total == 0 {
+            return 0.0;
+        }
+        (vocabulary.len() as f32 / total as f32).clamp(0.0, 1.0)
+    }
+
+    /// Occurrences of each word, lowercased and stripped of surrounding
+    /// punctuation (so "don't" keeps its apostrophe). Computed on demand.
+    pub fn word_frequency_table(&self) -> HashMap<String, u32> {
+        let mut frequencies = HashMap::new();
+        crate::tokenizer::count_word_frequencies(&self.words, &mut frequencies);
+        frequencies
+    }
+
+    /// `words[start..end]`, or `None` if `start > end` or `end` is past the
+    /// last word.
+    pub fn words_in_range(&self, start: usize, end: usize) -> Option<&[Word]> {
+        self.words.get(start..end)
+    }
+
+    /// Stats for `words[start..end]`, e.g. the window of words on screen.
+    /// `None` under the same conditions as `words_in_range`.
+    pub fn stats_for_range(&self, start: usize, end: usize) -> Option<ChapterStats> {
+        self.words_in_range(start, end).map(ChapterStats::from_words)
+    }
+
+    /// Index of the first word at or after `from_index` whose
+    /// `strip_punctuation_text` equals `query`, for "find next". `None` if
+    /// there is no match, `query` is empty, or `from_index` is past the end.
+    pub fn find_word(&self, query: &str, from_index: usize, case_sensitive: bool) -> Option<usize> {
+        let matches = word_matcher(query, case_sensitive)?;
+        let words = self.words.get(from_index..)?;
+        words.iter().position(matches).map(|i| from_index + i)
+    }
+
+    /// Index of the last word at or before `from_index` whose
+    /// `strip_punctuation_text` equals `query`, for "find previous". A
+    /// `from_index` past the end searches from the last word.
+    pub fn find_word_backward(&self, query: &str, from_index: usize, case_sensitive: bool) -> Option<usize> {
+        let matches = word_matcher(query, case_sensitive)?;
+        let end = from_index.saturating_add(1).min(self.words.len());
+        self.words[..end].iter().rposition(matches)
+    }
+
+    /// Printed pages this chapter would fill at `words_per_page` (see
+    /// `DEFAULT_WORDS_PER_PAGE`), rounded up; 0 for an empty chapter.
+    pub fn approximate_page_count(&self, words_per_page: u32) -> u32 {
+        page_count(self.stats.word_count, words_per_page)
+    }
+
+    /// Chapter as plain text: a `\n=== Title ===\n` header, then the words
+    /// separated by spaces with a blank line after each paragraph.
+    /// Split chunks are rejoined, so re-tokenizing gives the same words.
+    pub fn to_plain_text(&self) -> String {
+        let mut out = format!("\n=== {} ===\n", self.title);
+        let mut at_line_start = true;
+
+        for word in self.words.iter().filter(|w| !w.is_pause()) {
+            let continues_chunk = word.is_chunk && word.chunk_role != ChunkRole::First;
+            if !continues_chunk && !at_line_start {
+                out.push(' ');
+            }
+            if word.is_chunk {
+                out.push_str(word.text.trim_matches('-'));
+                // Chunks are letters only; restore the mark the whole word ended with
+                if word.chunk_role == ChunkRole::Last {
+                    out.extend(word.following_punct.as_char());
+                }
+            } else {
+                out.push_str(&word.text);
+            }
+            at_line_start = false;
+
+            if word.following_punct == Punctuation::Paragraph {
+                out.push_str("\n\n");
+                at_line_start = true;
+            }
+        }
+
+        if !at_line_start {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Predicate for `Chapter::find_word`; `None` for an empty query.
+fn word_matcher(query: &str, case_sensitive: bool) -> Option<impl Fn(&Word) -> bool + '_> {
+    if query.is_empty() {
+        return None;
+    }
+    let lowered = if case_sensitive { String::new() } else { query.to_lowercase() };
+    Some(move |word: &Word| {
+        let text = word.strip_punctuation_text();
+        if case_sensitive {
+            text == query
+        } else {
+            text.to_lowercase() == lowered
+        }
+    })
+}
+
+/// "Chapter N: Title (M words)", with N counted from 1 (the spine position,
+/// so it can skip numbers when spine items were dropped).
+impl std::fmt::Display for Chapter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let words = self.stats.word_count;
+        let noun = if words == 1 { "word" } else { "words" };
+        write!(f, "Chapter {}: {} ({} {})", self.index + 1, self.title, words, noun)
+    }
 }
 
 /// Book metadata
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct BookMetadata {
     pub title: String,
+    /// First `dc:creator`
     pub author: Option<String>,
+    /// Any further `dc:creator`s, in document order (co-authors, editors of
+    /// an anthology, ...)
+    pub additional_authors: Vec<String>,
+    /// Series name (EPUB 3 `belongs-to-collection`, else `calibre:series`)
+    pub series: Option<String>,
+    /// Position in the series; fractional for e.g. novellas (1.5)
+    pub series_index: Option<f32>,
+    /// From the OPF `<package version>` attribute
+    pub epub_version: EpubVersion,
+    /// True if the book, or any page of it, is fixed-layout
+    /// (`rendition:layout` pre-paginated, or `page-spread-*` spine properties)
+    pub is_fixed_layout: bool,
+    /// EPUB 3 `<meta property="dcterms:modified">`, as written (ISO 8601)
+    pub modified_date: Option<String>,
+    /// EPUB 3 `<meta property="schema:numberOfPages">`: pages in the print edition
+    pub page_count: Option<u32>,
+    /// Any other top-level EPUB 3 `<meta property>`, by property name
+    pub extra_meta: HashMap<String, String>,
+    /// Article the title starts with ("The", "Die", ...), as written; see
+    /// `Book::title_normalized`
+    pub leading_article: Option<String>,
+    /// The `dc:identifier` named by `<package unique-identifier>` (ISBN,
+    /// UUID, ...), or the first `dc:identifier` if none matches
+    pub unique_id: Option<String>,
+}
+
+/// Articles stripped by `Book::title_normalized`: English, German, French
+/// and Spanish.
+const LEADING_ARTICLES: &[&str] = &["The", "A", "An", "Der", "Die", "Das", "Le", "La", "Les", "El", "Los", "Las"];
+
+/// Split a title into its leading article (matched case-insensitively) and
+/// the rest. A title that is only an article has none.
+pub(crate) fn split_leading_article(title: &str) -> (Option<&str>, &str) {
+    let title = title.trim_start();
+    if let Some((first, rest)) = title.split_once(char::is_whitespace) {
+        let rest = rest.trim_start();
+        if !rest.is_empty() && LEADING_ARTICLES.iter().any(|article| article.eq_ignore_ascii_case(first)) {
+            return (Some(first), rest);
+        }
+    }
+    (None, title)
+}
+
+impl BookMetadata {
+    /// All creators: `author` followed by `additional_authors`.
+    pub fn authors(&self) -> impl Iterator<Item = &str> {
+        self.author.iter().chain(&self.additional_authors).map(String::as_str)
+    }
+}
+
+/// "Title by Author" ("by A, B" for several authors), or just "Title".
+impl std::fmt::Display for BookMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.title)?;
+        for (i, author) in self.authors().enumerate() {
+            f.write_str(if i == 0 { " by " } else { ", " })?;
+            f.write_str(author)?;
+        }
+        Ok(())
+    }
+}
+
+/// EPUB spec version a book declares
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EpubVersion {
+    /// 2.0 / 2.0.1
+    Epub2,
+    /// 3.0 and later 3.x
+    Epub3,
+    /// Any other `version` value, kept verbatim (empty if missing)
+    Unknown(String),
+}
+
+impl EpubVersion {
+    /// Classify an OPF `<package version="...">` value.
+    pub fn from_version_attr(version: &str) -> Self {
+        let version = version.trim();
+        match version.split('.').next() {
+            Some("2") => EpubVersion::Epub2,
+            Some("3") => EpubVersion::Epub3,
+            _ => EpubVersion::Unknown(version.to_string()),
+        }
+    }
+}
+
+impl Default for EpubVersion {
+    fn default() -> Self {
+        EpubVersion::Unknown(String::new())
+    }
+}
+
+/// EPUB 2 `<guide>` references to well-known pages (hrefs relative to the OPF)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpubGuide {
+    /// `<reference type="cover">`
+    pub cover_href: Option<String>,
+    /// `<reference type="toc">`
+    pub toc_href: Option<String>,
+    /// `<reference type="text">`: where the actual reading content starts
+    pub text_start_href: Option<String>,
 }
 
 /// Aggregated book statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct BookStats {
     pub total_words: u32,
     pub aggregated: ChapterStats,
+    /// Global word offset of the first word of each chapter (prefix sums).
+    /// Mirrors `Chapter::global_word_start`; kept sorted for O(log n) lookup
+    /// in `Book::chapter_at_word_offset`.
+    pub chapter_offsets: Vec<u32>,
+}
+
+/// A saved place in a book: the chapter and word to resume at, plus the
+/// matching `Book::reading_progress` so a library view can show it
+/// without loading the book. See `Book::position_from_offset`.
+///
+/// `word_index` indexes `Chapter::words`, pause words included, whereas
+/// global word offsets count real words only; converting between the two
+/// needs the chapters' words, so `Book` does it. The JNI `positionToOffset`
+/// pair works from word counts alone and agrees only without pause words.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReadingPosition {
+    /// Position in `Book::chapters`
+    pub chapter_index: u32,
+    /// Index into that chapter's `words` (pause words included)
+    pub word_index: u32,
+    /// Fraction of the book read, 0.0-1.0
+    pub progress: f32,
 }
 
 impl BookStats {
     pub fn from_chapters(chapters: &[Chapter]) -> Self {
         let mut aggregated = ChapterStats::default();
+        let mut chapter_offsets = Vec::with_capacity(chapters.len());
         for chapter in chapters {
+            chapter_offsets.push(aggregated.word_count);
             aggregated.merge(&chapter.stats);
         }
         BookStats {
             total_words: aggregated.word_count,
             aggregated,
+            chapter_offsets,
+        }
+    }
+
+    /// `word_count` of each chapter, from the gaps between `chapter_offsets`;
+    /// e.g. for drawing chapters as proportional segments of a progress bar.
+    pub fn chapter_word_counts(&self) -> Vec<u32> {
+        (0..self.chapter_offsets.len()).filter_map(|i| self.chapter_word_count(i)).collect()
+    }
+
+    /// `word_count` of the chapter at `index`, or `None` if out of range.
+    fn chapter_word_count(&self, index: usize) -> Option<u32> {
+        let start = *self.chapter_offsets.get(index)?;
+        let end = self.chapter_offsets.get(index + 1).copied().unwrap_or(self.total_words);
+        Some(end.saturating_sub(start))
+    }
+
+    /// Fraction of the book (0.0-1.0) read at a global word offset.
+    /// Offsets past the end clamp to 1.0; an empty book is always at 0.0.
+    pub fn reading_progress(&self, global_word_offset: u32) -> f32 {
+        if self.total_words == 0 {
+            return 0.0;
+        }
+        global_word_offset.min(self.total_words) as f32 / self.total_words as f32
+    }
+
+    /// Nearest global word offset for a progress fraction (inverse of `reading_progress`).
+    /// The fraction is clamped to 0.0-1.0; NaN maps to the start of the book.
+    pub fn word_offset_for_progress(&self, fraction: f32) -> u32 {
+        if self.total_words == 0 || fraction.is_nan() {
+            return 0;
+        }
+        let fraction = fraction.clamp(0.0, 1.0) as f64;
+        ((fraction * self.total_words as f64).round() as u32).min(self.total_words)
+    }
+
+    /// Time to read the whole book at `wpm` words per minute, in seconds.
+    pub fn estimated_reading_time_seconds(&self, wpm: u32) -> f32 {
+        self.aggregated.estimated_reading_time_seconds(wpm)
+    }
+
+    /// Reading time for the whole book at `wpm` as "3h 14m", "42m" or "< 1m".
+    pub fn estimated_reading_time_formatted(&self, wpm: u32) -> String {
+        self.aggregated.estimated_reading_time_formatted(wpm)
+    }
+}
+
+fn format_reading_time(seconds: f32) -> String {
+    if seconds < 60.0 {
+        return "< 1m".to_string();
+    }
+    let minutes = (seconds / 60.0).round() as u64;
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+/// A non-fatal problem found while parsing an EPUB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// Spine item whose manifest entry or ZIP file is missing. `href` is the
+    /// resolved archive path, or empty if the idref is not in the manifest.
+    MissingSpineItem { item_id: String, href: String },
+    /// Metadata element that is present but unusable (e.g. empty `dc:creator`)
+    MalformedMetadata(String),
+    /// Spine item (by spine index) that produced no readable text
+    EmptyChapter(usize),
+    /// XHTML file that was read despite XML errors (path and error)
+    MalformedXhtml(String),
+    /// `META-INF/container.xml` was missing; the OPF at this path was used instead
+    MissingContainer(String),
+    /// Fixed-layout spine item (by spine index) not read because of
+    /// `ParseConfig::skip_fixed_layout`
+    FixedLayoutSkipped(usize),
+    /// Spine item (by spine index) before the guide's `text` reference, not
+    /// read because of `ParseConfig::skip_front_matter`. Only returned by
+    /// `LazyBook::get_chapter`; `parse_epub` leaves these out silently.
+    FrontMatterSkipped(usize),
+}
+
+impl std::fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseWarning::MissingSpineItem { item_id, href } if href.is_empty() => {
+                write!(f, "Missing spine item: {} (not in manifest)", item_id)
+            }
+            ParseWarning::MissingSpineItem { item_id, href } => write!(f, "Missing spine item: {} ({})", item_id, href),
+            ParseWarning::MalformedMetadata(msg) => write!(f, "Malformed metadata: {}", msg),
+            ParseWarning::EmptyChapter(index) => write!(f, "Empty chapter at spine index {}", index),
+            ParseWarning::MalformedXhtml(msg) => write!(f, "Malformed XHTML: {}", msg),
+            ParseWarning::MissingContainer(path) => {
+                write!(f, "Missing container.xml, using OPF found at {}", path)
+            }
+            ParseWarning::FixedLayoutSkipped(index) => write!(f, "Skipped fixed-layout page at spine index {}", index),
+            ParseWarning::FrontMatterSkipped(index) => write!(f, "Skipped front matter at spine index {}", index),
         }
     }
 }
 
 /// A fully parsed book ready for the reader
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Book {
     pub metadata: BookMetadata,
+    pub guide: EpubGuide,
     pub chapters: Vec<Chapter>,
     pub stats: BookStats,
+    /// Non-fatal issues encountered while parsing
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// Same metadata and stats, and the same number of chapters with the same
+/// word counts. Word texts, the guide and warnings are not compared; use
+/// `Book::diff` to see what changed between two parses.
+impl PartialEq for Book {
+    fn eq(&self, other: &Self) -> bool {
+        self.metadata == other.metadata
+            && self.stats == other.stats
+            && self.chapters.len() == other.chapters.len()
+            && self.chapters.iter().zip(&other.chapters).all(|(a, b)| a.words.len() == b.words.len())
+    }
+}
+
+/// Multi-line summary: the metadata, totals, then one indented line per chapter.
+impl std::fmt::Display for Book {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.metadata)?;
+        write!(f, "{} chapters, {} words", self.chapters.len(), self.stats.total_words)?;
+        for chapter in &self.chapters {
+            write!(f, "\n  {}", chapter)?;
+        }
+        Ok(())
+    }
+}
+
+/// A word together with its position in the book
+#[derive(Debug, Clone, Copy)]
+pub struct WordRef<'a> {
+    /// Position in `Book::chapters`
+    pub chapter_index: usize,
+    /// Position in `Chapter::words`
+    pub word_index: usize,
+    pub word: &'a Word,
+}
+
+/// Flat iterator over the words of a book, see `Book::iter_words`
+#[derive(Debug, Clone)]
+pub struct BookWords<'a> {
+    chapters: &'a [Chapter],
+    chapter_index: usize,
+    word_index: usize,
+    remaining: usize,
+}
+
+impl<'a> Iterator for BookWords<'a> {
+    type Item = WordRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(chapter) = self.chapters.get(self.chapter_index) {
+            if let Some(word) = chapter.words.get(self.word_index) {
+                if word.is_pause() {
+                    self.word_index += 1;
+                    continue;
+                }
+                let item = WordRef {
+                    chapter_index: self.chapter_index,
+                    word_index: self.word_index,
+                    word,
+                };
+                self.word_index += 1;
+                self.remaining = self.remaining.saturating_sub(1);
+                return Some(item);
+            }
+            self.chapter_index += 1;
+            self.word_index = 0;
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for BookWords<'_> {}
+
+/// What changed between two parses of the same book (see `Book::diff`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookDiff {
+    /// Words in the new version that aren't matched in the old one
+    pub added_words: u32,
+    /// Words in the old version that aren't matched in the new one
+    pub removed_words: u32,
+    /// Positions in `chapters` whose words differ (including chapters present
+    /// in only one version)
+    pub changed_chapters: Vec<usize>,
+}
+
+/// Errors from `Book::split_at_chapter`
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SplitError {
+    #[error("Book has only one chapter")]
+    SingleChapter,
+    #[error("Cannot split before the first chapter")]
+    AtStart,
+    #[error("Chapter {index} is out of range for a book with {len} chapters")]
+    OutOfRange { index: usize, len: usize },
+}
+
+impl Book {
+    /// Iterate over every word in reading order, with its chapter and position.
+    /// Lazy; the length comes from `stats.total_words`.
+    pub fn iter_words(&self) -> BookWords<'_> {
+        BookWords {
+            chapters: &self.chapters,
+            chapter_index: 0,
+            word_index: 0,
+            remaining: self.stats.total_words as usize,
+        }
+    }
+
+    /// Printed pages the book would fill at `words_per_page`, rounded up.
+    /// Counted over the whole text, so it can be less than the sum of the
+    /// chapters' page counts.
+    pub fn approximate_page_count(&self, words_per_page: u32) -> u32 {
+        page_count(self.stats.total_words, words_per_page)
+    }
+
+    /// 1-based page holding a global word offset, for "page N of M" with
+    /// M from `approximate_page_count`. Offsets past the end give the last page.
+    pub fn approximate_page_for_word_offset(&self, global_offset: u32, words_per_page: u32) -> u32 {
+        let page = global_offset / words_per_page.max(1) + 1;
+        page.min(self.approximate_page_count(words_per_page).max(1))
+    }
+
+    /// Stable 64-bit hash of the title, authors and series, for spotting a
+    /// book imported twice when it has no `metadata.unique_id`. Case and
+    /// surrounding whitespace are ignored. FNV-1a, so the value is the same
+    /// across builds and can be stored.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let metadata = &self.metadata;
+        let fields = std::iter::once(metadata.title.as_str())
+            .chain(metadata.authors())
+            .chain(metadata.series.as_deref());
+        let mut hash = FNV_OFFSET;
+        for field in fields {
+            // Unit separator between fields, so ("ab", "c") != ("a", "bc")
+            for byte in field.trim().to_lowercase().bytes().chain([0x1f]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        hash
+    }
+
+    /// Title for alphabetical sorting, without a leading article:
+    /// "The Great Gatsby" sorts as "Great Gatsby", "Die Verwandlung" as
+    /// "Verwandlung".
+    pub fn title_normalized(&self) -> String {
+        split_leading_article(&self.metadata.title).1.to_string()
+    }
+
+    /// The whole book as plain text, chapter by chapter (see `Chapter::to_plain_text`).
+    pub fn to_plain_text(&self) -> String {
+        self.chapters.iter().map(Chapter::to_plain_text).collect()
+    }
+
+    /// Like `iter_words`, but starting at a global word offset.
+    /// Empty if the offset is past the end of the book.
+    pub fn iter_words_from(&self, global_offset: u32) -> BookWords<'_> {
+        let (chapter_index, word_index) = self
+            .local_word_index(global_offset)
+            .unwrap_or((self.chapters.len(), 0));
+        BookWords {
+            chapters: &self.chapters,
+            chapter_index,
+            word_index,
+            remaining: self.stats.total_words.saturating_sub(global_offset) as usize,
+        }
+    }
+
+    /// Distinct words across all chapters (see `tokenizer::unique_word_count`).
+    pub fn unique_word_count(&self) -> u32 {
+        let mut vocabulary = HashSet::new();
+        for chapter in &self.chapters {
+            crate::tokenizer::collect_vocabulary(&chapter.words, &mut vocabulary);
+        }
+        vocabulary.len() as u32
+    }
+
+    /// Occurrences of each word across all chapters (see `Chapter::word_frequency_table`).
+    pub fn word_frequency_table(&self) -> HashMap<String, u32> {
+        let mut frequencies = HashMap::new();
+        for chapter in &self.chapters {
+            crate::tokenizer::count_word_frequencies(&chapter.words, &mut frequencies);
+        }
+        frequencies
+    }
+
+    /// The `n` most frequent words, by count descending. Ties are broken
+    /// alphabetically so the result is deterministic.
+    pub fn top_n_words(&self, n: usize) -> Vec<(String, u32)> {
+        let mut words: Vec<(String, u32)> = self.word_frequency_table().into_iter().collect();
+        words.sort_unstable_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        words.truncate(n);
+        words
+    }
+
+    /// Fraction of the book (0.0-1.0) read at a global word offset.
+    pub fn reading_progress(&self, global_word_offset: u32) -> f32 {
+        self.stats.reading_progress(global_word_offset)
+    }
+
+    /// Nearest global word offset for a progress fraction.
+    pub fn word_offset_for_progress(&self, fraction: f32) -> u32 {
+        self.stats.word_offset_for_progress(fraction)
+    }
+
+    /// Position in `chapters` of the chapter containing a global word offset.
+    /// O(log n) over `stats.chapter_offsets`; `None` past the last word.
+    pub fn chapter_at_word_offset(&self, global_offset: u32) -> Option<usize> {
+        if global_offset >= self.stats.total_words {
+            return None;
+        }
+        // Last chapter starting at or before the offset (skips empty chapters)
+        let after = self
+            .stats
+            .chapter_offsets
+            .partition_point(|&start| start <= global_offset);
+        after.checked_sub(1)
+    }
+
+    /// `chapters[start..end]`, or `None` instead of panicking when
+    /// `start > end` or `end` is past the last chapter.
+    pub fn chapters_range(&self, start: usize, end: usize) -> Option<&[Chapter]> {
+        self.chapters.get(start..end)
+    }
+
+    pub fn first_chapter(&self) -> Option<&Chapter> {
+        self.chapters.first()
+    }
+
+    pub fn last_chapter(&self) -> Option<&Chapter> {
+        self.chapters.last()
+    }
+
+    /// First chapter whose title equals `title`, ignoring case.
+    pub fn chapter_by_title(&self, title: &str) -> Option<&Chapter> {
+        let title = title.to_lowercase();
+        self.chapters.iter().find(|c| c.title.to_lowercase() == title)
+    }
+
+    /// Chapter whose title is most similar to `title` (case-insensitive
+    /// Jaro-Winkler, 0.0-1.0), if that similarity is at least `threshold`
+    /// (`DEFAULT_TITLE_SIMILARITY` suits most books). Ties go to the earlier
+    /// chapter.
+    pub fn chapter_by_title_fuzzy(&self, title: &str, threshold: f32) -> Option<&Chapter> {
+        let title: Vec<char> = title.to_lowercase().chars().collect();
+        let mut best: Option<(&Chapter, f32)> = None;
+        for chapter in &self.chapters {
+            let candidate: Vec<char> = chapter.title.to_lowercase().chars().collect();
+            let similarity = jaro_winkler(&title, &candidate);
+            if similarity >= threshold && best.is_none_or(|(_, s)| similarity > s) {
+                best = Some((chapter, similarity));
+            }
+        }
+        best.map(|(chapter, _)| chapter)
+    }
+
+    /// Append `other`'s chapters to this book, e.g. to read a multi-volume
+    /// series as one stream.
+    ///
+    /// Appended chapters are renumbered to follow this book's, and stats and
+    /// `global_word_start` offsets are recomputed. Metadata and guide are
+    /// kept from `self`, with the title set to `"<self title> + <other title>"`;
+    /// warnings from both books are kept.
+    #[must_use]
+    pub fn merge(mut self, other: Book) -> Book {
+        self.metadata.title = format!("{} + {}", self.metadata.title, other.metadata.title);
+        let first_index = self.chapters.len() as u32;
+        self.chapters.extend(other.chapters.into_iter().enumerate().map(|(i, mut chapter)| {
+            chapter.index = first_index + i as u32;
+            chapter
+        }));
+        self.warnings.extend(other.warnings);
+        self.recompute_stats();
+        self
+    }
+
+    /// Split into two books at a chapter boundary, e.g. to separate the
+    /// volumes of an omnibus: chapters `..index` and `index..`, as with
+    /// `take_chapters`. Both halves must be non-empty.
+    pub fn split_at_chapter(&self, index: usize) -> Result<(Book, Book), SplitError> {
+        let len = self.chapters.len();
+        if len == 1 {
+            return Err(SplitError::SingleChapter);
+        }
+        if index == 0 {
+            return Err(SplitError::AtStart);
+        }
+        if index >= len {
+            return Err(SplitError::OutOfRange { index, len });
+        }
+        Ok((self.take_chapters(0..index), self.take_chapters(index..len)))
+    }
+
+    /// A book with just the chapters in `range` (clamped to the chapter
+    /// count), renumbered from 0, with stats and `global_word_start`
+    /// offsets recomputed. Metadata, guide and warnings are copied.
+    #[must_use]
+    pub fn take_chapters(&self, range: Range<usize>) -> Book {
+        let end = range.end.min(self.chapters.len());
+        let start = range.start.min(end);
+        let mut book = Book {
+            metadata: self.metadata.clone(),
+            guide: self.guide.clone(),
+            chapters: self.chapters[start..end].to_vec(),
+            stats: BookStats::default(),
+            warnings: self.warnings.clone(),
+        };
+        for (i, chapter) in book.chapters.iter_mut().enumerate() {
+            chapter.index = i as u32;
+        }
+        book.recompute_stats();
+        book
+    }
+
+    /// Rebuild `stats` and each chapter's `global_word_start` after the
+    /// chapter list changed.
+    fn recompute_stats(&mut self) {
+        self.stats = BookStats::from_chapters(&self.chapters);
+        for (chapter, &start) in self.chapters.iter_mut().zip(&self.stats.chapter_offsets) {
+            chapter.global_word_start = start;
+        }
+    }
+
+    /// Merge books in order with `merge`. An empty list gives an empty book.
+    #[must_use]
+    pub fn merge_all(books: Vec<Book>) -> Book {
+        books.into_iter().reduce(Book::merge).unwrap_or_default()
+    }
+
+    /// Compare against a newer parse of the same book.
+    ///
+    /// Chapters are paired by position. For a changed pair the word texts are
+    /// aligned with a longest common subsequence, so an edit in the middle of a
+    /// chapter only counts the words actually added or removed.
+    pub fn diff(&self, other: &Book) -> BookDiff {
+        let mut diff = BookDiff::default();
+        let chapter_count = self.chapters.len().max(other.chapters.len());
+
+        for i in 0..chapter_count {
+            let old = self.chapters.get(i).map(chapter_texts).unwrap_or_default();
+            let new = other.chapters.get(i).map(chapter_texts).unwrap_or_default();
+            if old == new {
+                continue;
+            }
+            let common = lcs_len(&old, &new);
+            diff.removed_words += (old.len() - common) as u32;
+            diff.added_words += (new.len() - common) as u32;
+            diff.changed_chapters.push(i);
+        }
+
+        diff
+    }
+
+    /// Reading position of a global word offset, with `word_index` into
+    /// `Chapter::words` (pause words included). Offsets past the end give
+    /// the end of the last chapter.
+    pub fn position_from_offset(&self, global_offset: u32) -> ReadingPosition {
+        let progress = self.reading_progress(global_offset);
+        let (chapter, word) = self.local_word_index(global_offset).unwrap_or_else(|| {
+            let last = self.chapters.len().saturating_sub(1);
+            (last, self.chapters.get(last).map_or(0, |c| c.words.len()))
+        });
+        ReadingPosition {
+            chapter_index: chapter as u32,
+            word_index: word as u32,
+            progress,
+        }
+    }
+
+    /// Global word offset of a reading position (inverse of
+    /// `position_from_offset`); `progress` is ignored. Pause words before
+    /// `word_index` are not counted. A word index past the end of its
+    /// chapter is clamped to the chapter's end; a chapter past the last
+    /// gives `stats.total_words`.
+    pub fn offset_from_position(&self, position: &ReadingPosition) -> u32 {
+        let index = position.chapter_index as usize;
+        let (Some(chapter), Some(&start)) = (self.chapters.get(index), self.stats.chapter_offsets.get(index)) else {
+            return self.stats.total_words;
+        };
+        let end = (position.word_index as usize).min(chapter.words.len());
+        start + chapter.words[..end].iter().filter(|w| !w.is_pause()).count() as u32
+    }
+
+    /// (position in `chapters`, word index within that chapter) for a global word offset.
+    /// Global offsets count real words only; the returned index is into `Chapter::words`.
+    pub fn local_word_index(&self, global_offset: u32) -> Option<(usize, usize)> {
+        let chapter = self.chapter_at_word_offset(global_offset)?;
+        let local = (global_offset - self.stats.chapter_offsets[chapter]) as usize;
+        let words = &self.chapters[chapter].words;
+        if words.len() == self.chapters[chapter].stats.word_count as usize {
+            return Some((chapter, local));
+        }
+        // Offsets skip pause words; find the index of the local-th real word
+        let index = words.iter().enumerate().filter(|(_, w)| !w.is_pause()).nth(local)?.0;
+        Some((chapter, index))
+    }
+}
+
+fn chapter_texts(chapter: &Chapter) -> Vec<&str> {
+    chapter.words.iter().filter(|w| !w.is_pause()).map(|w| w.text.as_str()).collect()
+}
+
+/// Longest shared prefix that earns the Jaro-Winkler bonus
+const JARO_WINKLER_MAX_PREFIX: usize = 4;
+
+/// Jaro-Winkler bonus per shared prefix character (Winkler's standard 0.1)
+const JARO_WINKLER_PREFIX_SCALE: f32 = 0.1;
+
+/// Jaro-Winkler similarity: 1.0 for identical strings, 0.0 for nothing in
+/// common, with a bonus for a shared prefix of up to
+/// `JARO_WINKLER_MAX_PREFIX` characters.
+fn jaro_winkler(a: &[char], b: &[char]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    // Characters match if equal and no further apart than this
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+    for (i, &c) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == c {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    // Matched characters that are out of order, counted in pairs
+    let a_seq = a.iter().zip(&a_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let b_seq = b.iter().zip(&b_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let transpositions = a_seq.zip(b_seq).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f32;
+    let jaro = (m / a.len() as f32 + m / b.len() as f32 + (m - transpositions as f32) / m) / 3.0;
+    let prefix = a.iter().zip(b).take(JARO_WINKLER_MAX_PREFIX).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f32 * JARO_WINKLER_PREFIX_SCALE * (1.0 - jaro)
+}
+
+/// Length of the longest common subsequence of two word lists. The shared
+/// prefix and suffix are trimmed first, so small edits stay cheap.
+fn lcs_len(a: &[&str], b: &[&str]) -> usize {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    // Two-row DP over the remaining middle
+    let mut prev = vec![0usize; b.len() + 1];
+    let mut curr = vec![0usize; b.len() + 1];
+    for x in a {
+        for (j, y) in b.iter().enumerate() {
+            curr[j + 1] = if x == y { prev[j] + 1 } else { prev[j + 1].max(curr[j]) };
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prefix + suffix + prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::{create_chapter, create_chapter_with_config, ParagraphPauseMode, TokenizeConfig};
+
+    fn sample_book() -> Book {
+        let chapters = vec![
+            create_chapter(0, "One".to_string(), &["The quick brown fox.", "Jumps over."]),
+            create_chapter(1, "Two".to_string(), &["A lazy dog sleeps in the warm sun."]),
+            create_chapter(2, "Three".to_string(), &["End."]),
+        ];
+        let stats = BookStats::from_chapters(&chapters);
+        Book {
+            metadata: BookMetadata::default(),
+            guide: EpubGuide::default(),
+            chapters,
+            stats,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let mut book = sample_book();
+        book.metadata.title = "Sample".to_string();
+        assert_eq!(book.metadata.to_string(), "Sample");
+        book.metadata.author = Some("A. Author".to_string());
+        book.metadata.additional_authors = vec!["B. Writer".to_string()];
+        assert_eq!(book.metadata.to_string(), "Sample by A. Author, B. Writer");
+
+        assert_eq!(book.chapters[1].to_string(), "Chapter 2: Two (8 words)");
+        assert_eq!(
+            book.to_string(),
+            "Sample by A. Author, B. Writer\n\
+             3 chapters, 15 words\n  \
+             Chapter 1: One (6 words)\n  \
+             Chapter 2: Two (8 words)\n  \
+             Chapter 3: Three (1 word)"
+        );
+    }
+
+    #[test]
+    fn test_word_strip_punctuation_text() {
+        let words = crate::tokenizer::tokenize("\"Hello, world!\" Don't");
+        assert_eq!(words[0].strip_punctuation_text(), "Hello");
+        assert_eq!(words[1].strip_punctuation_text(), "world");
+        assert_eq!(words[2].strip_punctuation_text(), "Don't");
+        assert_eq!(words[2].clean_text(), "don't");
+
+        let chunk = Word { text: "-national-".to_string(), ..Word::pause() };
+        assert_eq!(chunk.strip_punctuation_text(), "national");
+        // Borrowed from the word's own text
+        assert!(std::ptr::eq(chunk.strip_punctuation_text().as_ptr(), chunk.text[1..].as_ptr()));
+        assert_eq!(Word::pause().strip_punctuation_text(), "");
+    }
+
+    #[test]
+    fn test_chapters_range() {
+        let book = sample_book();
+        let titles = |chapters: &[Chapter]| chapters.iter().map(|c| c.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(book.chapters_range(1, 3).unwrap()), vec!["Two", "Three"]);
+        assert_eq!(book.chapters_range(3, 3).map(<[Chapter]>::len), Some(0));
+        assert!(book.chapters_range(2, 1).is_none());
+        assert!(book.chapters_range(0, 4).is_none());
+
+        assert_eq!(book.first_chapter().unwrap().title, "One");
+        assert_eq!(book.last_chapter().unwrap().title, "Three");
+        assert!(Book::default().first_chapter().is_none());
+        assert!(Book::default().last_chapter().is_none());
+    }
+
+    #[test]
+    fn test_chapter_by_title() {
+        let book = sample_book();
+        assert_eq!(book.chapter_by_title("two").unwrap().index, 1);
+        assert_eq!(book.chapter_by_title("TWO").unwrap().index, 1);
+        assert!(book.chapter_by_title("Tw").is_none());
+
+        assert_eq!(book.chapter_by_title_fuzzy("Thre", DEFAULT_TITLE_SIMILARITY).unwrap().index, 2);
+        assert_eq!(book.chapter_by_title_fuzzy("one", 1.0).unwrap().index, 0);
+        assert!(book.chapter_by_title_fuzzy("Epilogue", DEFAULT_TITLE_SIMILARITY).is_none());
+    }
+
+    #[test]
+    fn test_jaro_winkler() {
+        let jw = |a: &str, b: &str| jaro_winkler(&a.chars().collect::<Vec<_>>(), &b.chars().collect::<Vec<_>>());
+        assert_eq!(jw("war", "war"), 1.0);
+        assert_eq!(jw("", "war"), 0.0);
+        assert_eq!(jw("abc", "xyz"), 0.0);
+        assert!((jw("martha", "marhta") - 0.961).abs() < 0.001);
+        assert!((jw("dixon", "dicksonx") - 0.813).abs() < 0.001);
+        assert!(jw("chapter 3: war", "war") < jw("chapter 3: war", "chapter 3 war"));
+    }
+
+    #[test]
+    fn test_word_syllable_count() {
+        let words = crate::tokenizer::tokenize("Beautiful rhythm");
+        assert_eq!(words[0].syllable_count(), 3);
+        assert_eq!(words[1].syllable_count(), 2);
+        assert_eq!(Word::pause().syllable_count(), 0);
+    }
+
+    #[test]
+    fn test_epub_version_from_version_attr() {
+        assert_eq!(EpubVersion::from_version_attr("2.0"), EpubVersion::Epub2);
+        assert_eq!(EpubVersion::from_version_attr("2.0.1"), EpubVersion::Epub2);
+        assert_eq!(EpubVersion::from_version_attr(" 3.0 "), EpubVersion::Epub3);
+        assert_eq!(EpubVersion::from_version_attr("3.3"), EpubVersion::Epub3);
+        assert_eq!(EpubVersion::from_version_attr("1.0"), EpubVersion::Unknown("1.0".to_string()));
+        assert_eq!(EpubVersion::default(), EpubVersion::Unknown(String::new()));
+    }
+
+    #[test]
+    fn test_word_frequency_table() {
+        let book = sample_book();
+        let table = book.word_frequency_table();
+        assert_eq!(table["the"], 2);
+        assert_eq!(table["fox"], 1);
+        assert!(!table.contains_key("fox."));
+        assert_eq!(book.top_n_words(1), vec![("the".to_string(), 2)]);
+
+        let chapter = create_chapter(0, "C".to_string(), &["Don't stop. \"Don't!\""]);
+        assert_eq!(chapter.word_frequency_table()["don't"], 2);
+    }
+
+    #[test]
+    fn test_reading_progress_round_trip() {
+        let book = sample_book();
+        let total = book.stats.total_words;
+        assert!(total > 0);
+
+        for offset in 0..=total {
+            let progress = book.reading_progress(offset);
+            assert!((0.0..=1.0).contains(&progress));
+            assert_eq!(book.word_offset_for_progress(progress), offset);
+        }
+    }
+
+    #[test]
+    fn test_chapter_at_word_offset() {
+        let book = sample_book();
+        assert_eq!(book.stats.chapter_offsets, vec![0, 6, 14]);
+        assert_eq!(book.stats.chapter_word_counts(), vec![6, 8, 1]);
+
+        // Every global offset maps back to the same word
+        let mut global = 0;
+        for (c, chapter) in book.chapters.iter().enumerate() {
+            for (w, word) in chapter.words.iter().enumerate() {
+                assert_eq!(book.chapter_at_word_offset(global), Some(c));
+                assert_eq!(book.local_word_index(global), Some((c, w)));
+                let (lc, lw) = book.local_word_index(global).unwrap();
+                assert_eq!(book.chapters[lc].words[lw].text, word.text);
+                global += 1;
+            }
+        }
+        assert_eq!(book.chapter_at_word_offset(global), None);
+        assert_eq!(book.local_word_index(global), None);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let mut book = sample_book();
+        book.metadata.title = "Dune".to_string();
+        book.metadata.author = Some("Frank Herbert".to_string());
+        let fingerprint = book.fingerprint();
+        assert_eq!(fingerprint, 0x9ec2_d191_cddf_8ec5);
+
+        let mut same = Book::default();
+        same.metadata.title = " DUNE ".to_string();
+        same.metadata.author = Some("frank herbert".to_string());
+        assert_eq!(same.fingerprint(), fingerprint);
+
+        same.metadata.additional_authors.push("Brian Herbert".to_string());
+        assert_ne!(same.fingerprint(), fingerprint);
+        book.metadata.title = "Dun".to_string();
+        book.metadata.author = Some("eFrank Herbert".to_string());
+        assert_ne!(book.fingerprint(), fingerprint);
+    }
+
+    #[test]
+    fn test_title_normalized() {
+        let mut book = sample_book();
+        let normalized = |book: &mut Book, title: &str| {
+            book.metadata.title = title.to_string();
+            book.title_normalized()
+        };
+        assert_eq!(normalized(&mut book, "The Great Gatsby"), "Great Gatsby");
+        assert_eq!(normalized(&mut book, "A Tale of Two Cities"), "Tale of Two Cities");
+        assert_eq!(normalized(&mut book, "An American Tragedy"), "American Tragedy");
+        assert_eq!(normalized(&mut book, "the  lowercase"), "lowercase");
+        assert_eq!(normalized(&mut book, "Theory of Everything"), "Theory of Everything");
+        assert_eq!(normalized(&mut book, "The"), "The");
+
+        assert_eq!(normalized(&mut book, "Der Process"), "Process");
+        assert_eq!(normalized(&mut book, "Die Verwandlung"), "Verwandlung");
+        assert_eq!(normalized(&mut book, "Das Schloss"), "Schloss");
+        assert_eq!(normalized(&mut book, "Dieser Weg"), "Dieser Weg");
+        assert_eq!(normalized(&mut book, "Les Misérables"), "Misérables");
+
+        assert_eq!(split_leading_article("Die Verwandlung"), (Some("Die"), "Verwandlung"));
+        assert_eq!(split_leading_article("Ulysses"), (None, "Ulysses"));
+    }
+
+    #[test]
+    fn test_approximate_page_count() {
+        let book = sample_book(); // chapters of 6, 8 and 1 words
+        assert_eq!(book.chapters[1].approximate_page_count(3), 3);
+        assert_eq!(book.chapters[2].approximate_page_count(DEFAULT_WORDS_PER_PAGE), 1);
+        assert_eq!(book.approximate_page_count(5), 3);
+        assert_eq!(book.approximate_page_count(DEFAULT_WORDS_PER_PAGE), 1);
+        assert_eq!(book.approximate_page_count(0), 15);
+
+        assert_eq!(book.approximate_page_for_word_offset(0, 5), 1);
+        assert_eq!(book.approximate_page_for_word_offset(4, 5), 1);
+        assert_eq!(book.approximate_page_for_word_offset(5, 5), 2);
+        assert_eq!(book.approximate_page_for_word_offset(14, 5), 3);
+        assert_eq!(book.approximate_page_for_word_offset(99, 5), 3);
+
+        let empty = Book::default();
+        assert_eq!(empty.approximate_page_count(DEFAULT_WORDS_PER_PAGE), 0);
+        assert_eq!(empty.approximate_page_for_word_offset(0, DEFAULT_WORDS_PER_PAGE), 1);
+    }
+
+    #[test]
+    fn test_split_at_chapter() {
+        let book = sample_book();
+        let (first, second) = book.split_at_chapter(1).unwrap();
+        assert_eq!(first.chapters.len(), 1);
+        assert_eq!(first.stats.total_words, 6);
+        assert_eq!(second.chapters.iter().map(|c| c.title.as_str()).collect::<Vec<_>>(), vec!["Two", "Three"]);
+        assert_eq!(second.chapters.iter().map(|c| c.index).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(second.chapters[1].global_word_start, 8);
+        assert_eq!(second.stats.chapter_offsets, vec![0, 8]);
+        assert_eq!(first.stats.total_words + second.stats.total_words, book.stats.total_words);
+        assert_eq!(first.merge(second).stats, book.stats);
+
+        assert_eq!(book.split_at_chapter(0).unwrap_err(), SplitError::AtStart);
+        assert_eq!(book.split_at_chapter(3).unwrap_err(), SplitError::OutOfRange { index: 3, len: 3 });
+        let single = book.take_chapters(2..3);
+        assert_eq!(single.split_at_chapter(1).unwrap_err(), SplitError::SingleChapter);
+
+        assert_eq!(book.take_chapters(1..99).chapters.len(), 2);
+        assert!(book.take_chapters(5..9).chapters.is_empty());
+        assert_eq!(book.take_chapters(5..9).stats, BookStats::default());
+    }
+
+    #[test]
+    fn test_reading_position() {
+        let book = sample_book();
+        let position = book.position_from_offset(7);
+        assert_eq!(position, ReadingPosition { chapter_index: 1, word_index: 1, progress: 7.0 / 15.0 });
+        assert_eq!(book.offset_from_position(&position), 7);
+
+        for offset in 0..=book.stats.total_words {
+            let position = book.position_from_offset(offset);
+            assert_eq!(book.offset_from_position(&position), offset);
+        }
+        let end = ReadingPosition { chapter_index: 2, word_index: 1, progress: 1.0 };
+        assert_eq!(book.position_from_offset(99), end);
+        assert_eq!(book.offset_from_position(&ReadingPosition { chapter_index: 0, word_index: 99, progress: 0.0 }), 6);
+        assert_eq!(book.offset_from_position(&ReadingPosition { chapter_index: 9, ..end }), 15);
+        assert_eq!(Book::default().position_from_offset(3), ReadingPosition::default());
+
+        // Word indices include pause words; offsets don't
+        let config = TokenizeConfig {
+            paragraph_pause_mode: ParagraphPauseMode::ProportionalToLength,
+            ..Default::default()
+        };
+        let chapters = vec![create_chapter_with_config(0, "Paused".to_string(), &["Short.", "Then more words here."], &config)];
+        let stats = BookStats::from_chapters(&chapters);
+        let paused = Book { chapters, stats, ..Default::default() };
+        assert!(paused.chapters[0].words[1].is_pause());
+        let position = paused.position_from_offset(1);
+        assert_eq!(position.word_index, 2);
+        assert_eq!(paused.chapters[0].words[2].text, "Then");
+        assert_eq!(paused.offset_from_position(&position), 1);
+    }
+
+    #[test]
+    fn test_iter_words() {
+        let book = sample_book();
+        let nested: Vec<(usize, usize, &str)> = book
+            .chapters
+            .iter()
+            .enumerate()
+            .flat_map(|(c, ch)| ch.words.iter().enumerate().map(move |(w, word)| (c, w, word.text.as_str())))
+            .collect();
+
+        let iter = book.iter_words();
+        assert_eq!(iter.len(), book.stats.total_words as usize);
+        let flat: Vec<(usize, usize, &str)> = iter
+            .map(|r| (r.chapter_index, r.word_index, r.word.text.as_str()))
+            .collect();
+        assert_eq!(flat, nested);
+
+        for offset in 0..=book.stats.total_words {
+            let from = book.iter_words_from(offset);
+            assert_eq!(from.len(), nested.len() - offset as usize);
+            let rest: Vec<(usize, usize, &str)> = from
+                .map(|r| (r.chapter_index, r.word_index, r.word.text.as_str()))
+                .collect();
+            assert_eq!(rest, nested[offset as usize..]);
+        }
+        assert_eq!(book.iter_words_from(book.stats.total_words + 5).count(), 0);
+    }
+
+    #[test]
+    fn test_reading_progress_clamps() {
+        let book = sample_book();
+        let total = book.stats.total_words;
+
+        assert_eq!(book.reading_progress(total + 100), 1.0);
+        assert_eq!(book.word_offset_for_progress(-0.5), 0);
+        assert_eq!(book.word_offset_for_progress(1.5), total);
+        assert_eq!(book.word_offset_for_progress(f32::NAN), 0);
+    }
+
+    #[test]
+    fn test_vocabulary_metrics() {
+        let book = sample_book();
+        // "The" appears in chapters 0 and 1 ("the")
+        let chapter_total: u32 = book
+            .chapters
+            .iter()
+            .map(|c| crate::tokenizer::unique_word_count(&c.words))
+            .sum();
+        assert_eq!(book.unique_word_count(), chapter_total - 1);
+
+        let repetitive = create_chapter(0, "R".to_string(), &["Go go go go."]);
+        assert_eq!(repetitive.vocabulary_richness(), 0.25);
+        assert_eq!(book.chapters[2].vocabulary_richness(), 1.0);
+    }
+
+    #[test]
+    fn test_words_in_range() {
+        let chapter = &sample_book().chapters[1]; // "A lazy dog sleeps in the warm sun."
+        let window = chapter.words_in_range(1, 3).unwrap();
+        assert_eq!(window.iter().map(|w| w.text.as_str()).collect::<Vec<_>>(), vec!["lazy", "dog"]);
+        assert_eq!(chapter.words_in_range(8, 8).map(<[Word]>::len), Some(0));
+        assert_eq!(chapter.words_in_range(0, 8).map(<[Word]>::len), Some(8));
+        assert!(chapter.words_in_range(0, 9).is_none());
+        assert!(chapter.words_in_range(3, 2).is_none());
+
+        let stats = chapter.stats_for_range(5, 8).unwrap();
+        assert_eq!(stats.word_count, 3);
+        assert_eq!(stats.punct_counts[Punctuation::Period as usize], 1);
+        assert_eq!(chapter.stats_for_range(0, 8), Some(chapter.stats.clone()));
+        assert_eq!(chapter.stats_for_range(9, 10), None);
+    }
+
+    #[test]
+    fn test_find_word() {
+        let chapter = create_chapter(0, "Find".to_string(), &["The cat saw the dog. \"The end,\" said the cat."]);
+        // The(0) cat(1) saw(2) the(3) dog.(4) "The(5) end,"(6) said(7) the(8) cat.(9)
+        assert_eq!(chapter.find_word("the", 0, true), Some(3));
+        assert_eq!(chapter.find_word("the", 0, false), Some(0));
+        assert_eq!(chapter.find_word("The", 1, true), Some(5));
+        assert_eq!(chapter.find_word("cat", 2, true), Some(9));
+        assert_eq!(chapter.find_word("cat", 9, true), Some(9));
+        assert_eq!(chapter.find_word("cat", 10, true), None);
+        assert_eq!(chapter.find_word("cat", usize::MAX, true), None);
+        assert_eq!(chapter.find_word("bird", 0, false), None);
+        assert_eq!(chapter.find_word("", 0, false), None);
+
+        assert_eq!(chapter.find_word_backward("cat", 8, true), Some(1));
+        assert_eq!(chapter.find_word_backward("CAT", usize::MAX, false), Some(9));
+        assert_eq!(chapter.find_word_backward("the", 4, false), Some(3));
+        assert_eq!(chapter.find_word_backward("The", 4, true), Some(0));
+        assert_eq!(chapter.find_word_backward("dog", 3, true), None);
+    }
+
+    #[test]
+    fn test_stats_add_word() {
+        let config = TokenizeConfig {
+            paragraph_pause_mode: ParagraphPauseMode::ProportionalToLength,
+            ..Default::default()
+        };
+        let chapter = create_chapter_with_config(0, "Stream".to_string(), &["One two, three.", "Four!"], &config);
+        assert!(chapter.words.iter().any(|w| w.is_pause()));
+
+        let mut streamed = ChapterStats::default();
+        for word in &chapter.words {
+            streamed.add_word(word);
+        }
+        assert_eq!(streamed, chapter.stats);
+        assert_eq!(streamed.word_count, 4);
+    }
+
+    #[test]
+    fn test_stats_subtract_window() {
+        let chapter = create_chapter(
+            0,
+            "Window".to_string(),
+            &["One two, three.", "Four five six! Seven?", "Eight nine internationalization."],
+        );
+        let words = &chapter.words;
+
+        for i in 0..=words.len() {
+            for j in i..=words.len() {
+                let prefix_i = crate::tokenizer::stats_for_range(&words[..i]);
+                let prefix_j = crate::tokenizer::stats_for_range(&words[..j]);
+                assert_eq!(prefix_j.subtract(&prefix_i), ChapterStats::from_words(&words[i..j]));
+            }
+        }
+
+        let empty = ChapterStats::default();
+        assert_eq!(empty.subtract(&chapter.stats), empty);
+    }
+
+    #[test]
+    fn test_readability_scores() {
+        let simple = create_chapter(0, "Simple".to_string(), &["The cat sat. The dog ran."]);
+        let complex = create_chapter(
+            1,
+            "Complex".to_string(),
+            &["Institutional considerations necessitate comprehensive evaluation of organizational methodologies."],
+        );
+
+        assert_eq!(simple.stats.sentence_count(), 2);
+        assert_eq!(simple.stats.syllable_count, 6);
+        assert!(simple.stats.flesch_kincaid_grade() < complex.stats.flesch_kincaid_grade());
+        assert!(simple.stats.flesch_reading_ease() > complex.stats.flesch_reading_ease());
+        assert_eq!(ChapterStats::default().flesch_kincaid_grade(), 0.0);
+    }
+
+    #[test]
+    fn test_reading_progress_empty_book() {
+        let book = Book {
+            metadata: BookMetadata::default(),
+            guide: EpubGuide::default(),
+            chapters: Vec::new(),
+            stats: BookStats::default(),
+            warnings: Vec::new(),
+        };
+        assert_eq!(book.reading_progress(10), 0.0);
+        assert_eq!(book.word_offset_for_progress(0.5), 0);
+    }
+
+    #[test]
+    fn test_to_plain_text() {
+        let book = sample_book();
+        let text = book.to_plain_text();
+        // A paragraph ending in a sentence mark keeps that mark instead of Paragraph
+        assert!(text.starts_with("\n=== One ===\nThe quick brown fox. Jumps over.\n"));
+        assert!(text.contains("=== Three ===\nEnd.\n"));
+
+        // "===" has no letters, so only the title words are added
+        let retokenized = crate::tokenizer::tokenize(&text);
+        assert_eq!(retokenized.len() as u32, book.stats.total_words + 3);
+    }
+
+    #[test]
+    fn test_to_plain_text_rejoins_chunks() {
+        let chapter = create_chapter(0, "Long".to_string(), &["Internationalization, indeed."]);
+        assert!(chapter.words[0].is_chunk);
+        assert_eq!(chapter.to_plain_text(), "\n=== Long ===\nInternationalization, indeed.\n");
+    }
+
+    #[test]
+    fn test_estimated_reading_time() {
+        let stats = ChapterStats {
+            word_count: 500,
+            ..Default::default()
+        };
+        assert_eq!(stats.estimated_reading_time_seconds(250), 120.0);
+        assert_eq!(stats.estimated_reading_time_seconds(0), 0.0);
+        assert_eq!(stats.estimated_reading_time_formatted(250), "2m");
+        assert_eq!(stats.estimated_reading_time_formatted(1000), "< 1m");
+
+        let book = BookStats {
+            total_words: 48_500,
+            aggregated: ChapterStats {
+                word_count: 48_500,
+                ..Default::default()
+            },
+            chapter_offsets: Vec::new(),
+        };
+        assert_eq!(book.estimated_reading_time_seconds(250), 11_640.0);
+        assert_eq!(book.estimated_reading_time_formatted(250), "3h 14m");
+        assert_eq!(BookStats::default().estimated_reading_time_formatted(250), "< 1m");
+    }
+
+    #[test]
+    fn test_display_duration_ms() {
+        let words = crate::tokenizer::tokenize("I reading, constantly. Internationalization");
+        // 300 wpm -> 200ms base
+        assert_eq!(words[0].display_duration_ms(300), 152); // Short
+        assert_eq!(words[1].display_duration_ms(300), 224 + 75); // Medium + comma
+        assert_eq!(words[2].display_duration_ms(300), 284 + 150); // Long + period
+        assert!(words[3].display_duration_ms(300) > words[3].display_duration_ms(600));
+        assert_eq!(words[0].display_duration_ms(0), words[0].display_duration_ms(1));
+
+        assert_eq!(LengthBucket::Short.display_multiplier(), 0.76);
+        assert_eq!(LengthBucket::VeryLong.display_multiplier(), 1.70);
+        assert_eq!(Punctuation::None.pause_ms(), 0);
+        assert_eq!(Punctuation::Period.pause_ms(), 150);
+        assert_eq!(Punctuation::Paragraph.pause_ms(), 300);
+    }
+
+    #[test]
+    fn test_timing_tables() {
+        // Pinned to the Kotlin TimingSettings values (see the SYNC: notes),
+        // which differ from the original request's numbers on purpose
+        let buckets = [LengthBucket::Short, LengthBucket::Medium, LengthBucket::Long, LengthBucket::VeryLong];
+        let multipliers = buckets.map(LengthBucket::display_multiplier);
+        assert_eq!(multipliers, [0.76, 1.12, 1.42, 1.70]);
+
+        // Natural preset: comma 75ms, sentence ends 150ms, paragraph 300ms
+        assert_eq!(Punctuation::None.pause_ms(), 0);
+        assert_eq!(Punctuation::Comma.pause_ms(), 75);
+        assert_eq!(Punctuation::Period.pause_ms(), 150);
+        assert_eq!(Punctuation::Exclamation.pause_ms(), 150);
+        assert_eq!(Punctuation::Question.pause_ms(), 150);
+        assert_eq!(Punctuation::Paragraph.pause_ms(), 300);
+    }
+
+    #[test]
+    fn test_split_chunk_duration() {
+        let words = crate::tokenizer::tokenize("Internationalization");
+        assert!(words.len() > 1 && words.iter().all(|w| w.is_chunk));
+        let whole = Word {
+            is_chunk: false,
+            ..words[0].clone()
+        };
+        let base_ms = whole.display_duration_ms(300) as f32;
+        assert_eq!(words[0].display_duration_ms(300), (base_ms * SPLIT_CHUNK_MULTIPLIER).round() as u32);
+    }
+
+    /// Value of `const val <name> = <value>` in Kotlin source, without a
+    /// trailing `f`.
+    fn kotlin_const(source: &str, name: &str) -> f32 {
+        let pattern = format!("const val {} = ", name);
+        let start = source.find(&pattern).unwrap_or_else(|| panic!("{} not in Timing.kt", name)) + pattern.len();
+        let value: String = source[start..].chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_timing_constants_match_kotlin() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../app/src/main/kotlin/app/spread/domain/Timing.kt");
+        let timing = std::fs::read_to_string(path).expect("Failed to read Timing.kt");
+
+        let names = ["SHORT_WORD_MULTIPLIER", "MEDIUM_WORD_MULTIPLIER", "LONG_WORD_MULTIPLIER", "VERY_LONG_WORD_MULTIPLIER"];
+        for (name, &multiplier) in names.iter().zip(&LENGTH_MULTIPLIERS) {
+            assert_eq!(kotlin_const(&timing, name), multiplier, "{}", name);
+        }
+        assert_eq!(kotlin_const(&timing, "DEFAULT_SPLIT_CHUNK_MULTIPLIER"), SPLIT_CHUNK_MULTIPLIER);
+
+        let natural = &timing[timing.find("val Natural = TimingSettings(").expect("Natural preset not in Timing.kt")..];
+        let natural = &natural[..natural.find(')').unwrap()];
+        let delay = |field: &str| -> u32 {
+            let line = natural.lines().find(|line| line.trim().starts_with(field)).unwrap();
+            line.split('=').nth(1).unwrap().trim().trim_end_matches(',').parse().unwrap()
+        };
+        assert_eq!(delay("commaDelayMs"), PUNCT_PAUSE_MS[Punctuation::Comma as usize]);
+        assert_eq!(delay("periodDelayMs"), PUNCT_PAUSE_MS[Punctuation::Period as usize]);
+        assert_eq!(delay("paragraphDelayMs"), PUNCT_PAUSE_MS[Punctuation::Paragraph as usize]);
+    }
+
+    #[test]
+    fn test_pause_words_skipped_by_offsets() {
+        let config = crate::tokenizer::TokenizeConfig {
+            paragraph_pause_mode: crate::tokenizer::ParagraphPauseMode::ProportionalToLength,
+            ..Default::default()
+        };
+        let chapter = crate::tokenizer::create_chapter_with_config(0, "P".to_string(), &["Heading", "Body text here now."], &config);
+        assert!(chapter.words[1].is_pause());
+        let chapters = vec![chapter];
+        let stats = BookStats::from_chapters(&chapters);
+        let book = Book {
+            metadata: BookMetadata::default(),
+            guide: EpubGuide::default(),
+            chapters,
+            stats,
+            warnings: Vec::new(),
+        };
+
+        assert_eq!(book.stats.total_words, 5);
+        let texts: Vec<&str> = book.iter_words().map(|w| w.word.text.as_str()).collect();
+        assert_eq!(texts, vec!["Heading", "Body", "text", "here", "now."]);
+        assert_eq!(book.local_word_index(1), Some((0, 2)));
+        assert_eq!(book.iter_words_from(1).next().unwrap().word.text, "Body");
+    }
+
+    #[test]
+    fn test_book_diff() {
+        let old = sample_book();
+        assert_eq!(old.diff(&old), BookDiff::default());
+
+        let chapters = vec![
+            create_chapter(0, "One".to_string(), &["The quick brown fox.", "Jumps over."]),
+            // "lazy" removed, "warm" -> "hot"
+            create_chapter(1, "Two".to_string(), &["A dog sleeps in the hot sun."]),
+            create_chapter(2, "Three".to_string(), &["End."]),
+            create_chapter(3, "Four".to_string(), &["Epilogue here."]),
+        ];
+        let stats = BookStats::from_chapters(&chapters);
+        let new = Book {
+            metadata: BookMetadata::default(),
+            guide: EpubGuide::default(),
+            chapters,
+            stats,
+            warnings: Vec::new(),
+        };
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.changed_chapters, vec![1, 3]);
+        assert_eq!(diff.removed_words, 2); // "lazy", "warm"
+        assert_eq!(diff.added_words, 3); // "hot", "Epilogue", "here."
+
+        let back = new.diff(&old);
+        assert_eq!((back.added_words, back.removed_words), (2, 3));
+    }
+
+    #[test]
+    fn test_merge_books() {
+        let volume = |title: &str| {
+            let mut book = sample_book();
+            book.metadata.title = title.to_string();
+            book
+        };
+        let mut second = volume("Volume 2");
+        second.warnings.push(ParseWarning::FixedLayoutSkipped(0));
+
+        let merged = volume("Volume 1").merge(second);
+        assert_eq!(merged.metadata.title, "Volume 1 + Volume 2");
+        assert_eq!(merged.chapters.len(), 6);
+        assert_eq!(merged.chapters.iter().map(|c| c.index).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(merged.stats.total_words, 30);
+        assert_eq!(merged.stats.chapter_offsets, vec![0, 6, 14, 15, 21, 29]);
+        assert_eq!(merged.chapters[3].global_word_start, 15);
+        assert_eq!(merged.warnings.len(), 1);
+        assert_eq!(merged.local_word_index(16), Some((3, 1)));
+
+        let all = Book::merge_all(vec![volume("A"), volume("B"), volume("C")]);
+        assert_eq!(all.metadata.title, "A + B + C");
+        assert_eq!(all.stats.total_words, 45);
+        assert!(Book::merge_all(Vec::new()).chapters.is_empty());
+    }
+
+    #[test]
+    fn test_orp_offset() {
+        let word = |text: &str| Word {
+            text: text.to_string(),
+            ..Word::pause()
+        };
+        assert_eq!(word("a").orp_offset(), 0);
+        assert_eq!(word("I").orp_offset(), 0);
+        // 5 letters -> 2nd letter
+        assert_eq!(word("quick").orp_offset(), 1);
+        // 10 letters -> 4th letter
+        assert_eq!(word("absolutely").orp_offset(), 3);
+        // 17 letters -> 6th letter
+        assert_eq!(word("uncharacteristic,").orp_offset(), 5);
+        // Leading hyphen of a chunk is skipped: "-ization" has 7 letters -> 3rd
+        let chunk = word("-ization");
+        assert_eq!(chunk.orp_offset(), 3);
+        assert_eq!(&chunk.text[chunk.orp_offset()..], "ation");
+        // Multi-byte letters give byte offsets
+        assert_eq!(word("éclairé").orp_offset(), "éc".len());
+        assert_eq!(Word::pause().orp_offset(), 0);
+    }
+
+    #[test]
+    fn test_bucket_and_punct_as_map_keys() {
+        use std::collections::HashMap;
+
+        let words = crate::tokenizer::tokenize("I am reading carefully, slowly. Extraordinarily!");
+        let mut by_bucket: HashMap<LengthBucket, u32> = HashMap::new();
+        let mut by_punct: HashMap<Punctuation, u32> = HashMap::new();
+        for word in &words {
+            *by_bucket.entry(word.length_bucket).or_default() += 1;
+            *by_punct.entry(word.following_punct).or_default() += 1;
+        }
+        assert_eq!(by_bucket[&LengthBucket::Short], 2);
+        assert_eq!(by_punct[&Punctuation::Comma], 1);
+
+        let mut buckets = vec![LengthBucket::VeryLong, LengthBucket::Short, LengthBucket::Long, LengthBucket::Medium];
+        buckets.sort();
+        assert_eq!(
+            buckets,
+            vec![LengthBucket::Short, LengthBucket::Medium, LengthBucket::Long, LengthBucket::VeryLong]
+        );
+        assert!(Punctuation::None < Punctuation::Comma);
+        assert!(Punctuation::Question < Punctuation::Paragraph);
+    }
 }